@@ -0,0 +1,707 @@
+//! Solve a travelling salesman problem read from a file
+//!
+//! Run `tsp-solve --help` for usage.
+//!
+extern crate time;
+extern crate travelling_salesman;
+
+#[cfg(feature = "serde")]
+extern crate serde_json;
+
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process;
+use std::slice;
+use std::time::Instant;
+use time::Duration;
+
+use travelling_salesman::metrics::{
+    CeilingEuclidean, Chebyshev, Euclidean, Geo, Haversine, Manhattan, Minkowski, RoundedEuclidean,
+    Vincenty,
+};
+use travelling_salesman::{
+    brute_force, geographic, get_distance_matrix_with_metric, hill_climbing, random_search, render,
+    simulated_annealing, tsplib, Error, Objective, Options, Shape, Tour,
+};
+
+const USAGE: &str = "Usage: tsp-solve [OPTIONS] INPUT
+
+Reads a problem from INPUT, or from standard input with -, searches for the shortest tour and
+prints its distance.
+
+Input formats, chosen by the file extension unless --format is given:
+  tsplib    TSPLIB instances (.tsp, .atsp)
+  csv       cities with x and y columns, or lat and lon columns (.csv)
+  json      problems in the travelling_salesman::json format (.json)
+
+Output formats, chosen by the file extension unless --output-format is given:
+  tsplib    TSPLIB tours (.tour)
+  json      solutions in the travelling_salesman::json format (.json)
+  csv       the cities in the order they are visited (.csv)
+  svg       a drawing of the tour (.svg)
+  png       a drawing of the tour, when built with the png feature (.png)
+
+Cities are numbered from 0, in the order they appear in the input.
+
+Options:
+  -s, --solver NAME            brute_force, hill_climbing, random_restarts, random_search or
+                               simulated_annealing (the default), where brute_force tries every
+                               route however long it takes, so only suits about a dozen cities
+  -t, --time SECONDS           how long to search for (default 1)
+      --iterations NUMBER      how many iterations to search for, instead of the time
+      --seed NUMBER            seed for the random choices of the search, which finds the same
+                               tour on every run when the iterations are given
+      --restart-probability P  how often random_restarts restarts (default 0.01)
+      --shape SHAPE            cycle (the default) or path
+      --start CITY             the city the route starts from
+      --end CITY               the city a path finishes at
+      --objective OBJECTIVE    distance (the default), bottleneck or longest
+      --metric METRIC          how distances between cities are measured: euclidean,
+                               rounded_euclidean, ceiling_euclidean, manhattan, chebyshev,
+                               minkowski:P (of order P), haversine or vincenty (in kilometres)
+                               or geo, which defaults to euclidean, or haversine for latitudes
+                               and longitudes
+      --optimum DISTANCE       the known optimal distance, to print the gap to it
+      --format FORMAT          the input format
+  -o, --output FILE            write the tour to FILE, or to standard output with -
+      --output-format FORMAT   the output format
+  -h, --help                   print this help
+";
+
+/// The options which take a value
+const FLAGS: [&str; 17] = [
+    "-s",
+    "--solver",
+    "-t",
+    "--time",
+    "--iterations",
+    "--seed",
+    "--restart-probability",
+    "--shape",
+    "--start",
+    "--end",
+    "--objective",
+    "--metric",
+    "--optimum",
+    "--format",
+    "-o",
+    "--output",
+    "--output-format",
+];
+
+/// The solvers which can be run by name
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Solver {
+    BruteForce,
+    HillClimbing,
+    RandomRestarts,
+    RandomSearch,
+    SimulatedAnnealing,
+}
+
+impl Solver {
+    fn from_name(name: &str) -> Result<Solver, String> {
+        match name {
+            "brute_force" => Ok(Solver::BruteForce),
+            "hill_climbing" => Ok(Solver::HillClimbing),
+            "random_restarts" => Ok(Solver::RandomRestarts),
+            "random_search" => Ok(Solver::RandomSearch),
+            "simulated_annealing" => Ok(Solver::SimulatedAnnealing),
+            other => Err(format!("unknown solver \"{}\"", other)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            Solver::BruteForce => "brute_force",
+            Solver::HillClimbing => "hill_climbing",
+            Solver::RandomRestarts => "random_restarts",
+            Solver::RandomSearch => "random_search",
+            Solver::SimulatedAnnealing => "simulated_annealing",
+        }
+    }
+}
+
+/// The command-line arguments, where anything not given falls back to the input or the defaults
+#[derive(Default)]
+struct Arguments {
+    input: Option<String>,
+    format: Option<String>,
+    solver: Option<Solver>,
+    seconds: Option<f64>,
+    iterations: Option<u64>,
+    seed: Option<u64>,
+    restart_probability: Option<f64>,
+    shape: Option<String>,
+    start: Option<usize>,
+    end: Option<usize>,
+    objective: Option<Objective>,
+    metric: Option<String>,
+    optimum: Option<f64>,
+    output: Option<String>,
+    output_format: Option<String>,
+    help: bool,
+}
+
+/// A problem read from the input, with any settings it gives
+struct Problem {
+    name: String,
+    cities: Vec<(f64, f64)>,
+    geographic: bool,
+    distances: Vec<Vec<f64>>,
+    solver: Solver,
+    runtime: Duration,
+    restart_probability: f64,
+    options: Options,
+}
+
+fn main() {
+    let arguments = match parse_arguments(env::args().skip(1)) {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            eprintln!("tsp-solve: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if arguments.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    if let Err(message) = run(&arguments) {
+        eprintln!("tsp-solve: {}", message);
+        process::exit(1);
+    }
+}
+
+fn parse_arguments<I: Iterator<Item = String>>(mut args: I) -> Result<Arguments, String> {
+    let mut arguments = Arguments::default();
+
+    while let Some(arg) = args.next() {
+        // values can be given as `--name value` or `--name=value`
+
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if arg.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+
+        if flag == "-h" || flag == "--help" {
+            arguments.help = true;
+            continue;
+        }
+
+        if !flag.starts_with('-') || flag == "-" {
+            if arguments.input.is_some() {
+                return Err(format!("unexpected argument \"{}\"", arg));
+            }
+
+            arguments.input = Some(arg);
+            continue;
+        }
+
+        if !FLAGS.contains(&flag.as_str()) {
+            return Err(format!("unknown option \"{}\"", flag));
+        }
+
+        let value = match inline_value.or_else(|| args.next()) {
+            Some(value) => value,
+            None => return Err(format!("{} needs a value", flag)),
+        };
+
+        match flag.as_str() {
+            "-s" | "--solver" => arguments.solver = Some(Solver::from_name(&value)?),
+            "-t" | "--time" => arguments.seconds = Some(parse_value(&flag, &value)?),
+            "--iterations" => arguments.iterations = Some(parse_value(&flag, &value)?),
+            "--seed" => arguments.seed = Some(parse_value(&flag, &value)?),
+            "--restart-probability" => {
+                arguments.restart_probability = Some(parse_value(&flag, &value)?)
+            }
+            "--shape" => arguments.shape = Some(value),
+            "--start" => arguments.start = Some(parse_value(&flag, &value)?),
+            "--end" => arguments.end = Some(parse_value(&flag, &value)?),
+            "--objective" => {
+                arguments.objective = Some(match value.as_str() {
+                    "distance" => Objective::MinimiseDistance,
+                    "bottleneck" => Objective::MinimiseLongestEdge,
+                    "longest" => Objective::MaximiseDistance,
+                    other => return Err(format!("unknown objective \"{}\"", other)),
+                })
+            }
+            "--metric" => arguments.metric = Some(value),
+            "--optimum" => arguments.optimum = Some(parse_value(&flag, &value)?),
+            "--format" => arguments.format = Some(value),
+            "-o" | "--output" => arguments.output = Some(value),
+            "--output-format" => arguments.output_format = Some(value),
+            _ => unreachable!(),
+        }
+    }
+
+    Ok(arguments)
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value \"{}\" for {}", value, flag))
+}
+
+/// Returns the format named by `format`, or else by the extension of `path`
+fn format_of(format: &Option<String>, path: &str) -> Result<String, String> {
+    if let Some(ref format) = *format {
+        return Ok(format.to_lowercase());
+    }
+
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+
+    match extension.as_deref() {
+        Some("tsp") | Some("atsp") | Some("tour") => Ok("tsplib".to_string()),
+        Some(extension @ "csv") | Some(extension @ "json") | Some(extension @ "svg") => {
+            Ok(extension.to_string())
+        }
+        #[cfg(feature = "png")]
+        Some("png") => Ok("png".to_string()),
+        _ => Err(format!(
+            "cannot tell the format of \"{}\" from its extension",
+            path
+        )),
+    }
+}
+
+fn run(arguments: &Arguments) -> Result<(), String> {
+    let input = match arguments.input {
+        Some(ref input) => input,
+        None => return Err("no input file given".to_string()),
+    };
+
+    let mut problem = read_problem(input, arguments)?;
+    apply_arguments(&mut problem, arguments)?;
+
+    let start_time = Instant::now();
+    let tour = solve(&problem).map_err(|error| error.to_string())?;
+    let elapsed = start_time.elapsed();
+
+    // the summary goes to standard error when the tour is written to standard output
+
+    let mut summary = format!(
+        "solver:   {}\ncities:   {}\ndistance: {}\n",
+        problem.solver.name(),
+        problem.distances.len(),
+        tour.distance
+    );
+
+    if let Some(optimum) = arguments.optimum {
+        let gap = if optimum == 0.0 {
+            0.0
+        } else {
+            (tour.distance - optimum) / optimum * 100.0
+        };
+
+        summary.push_str(&format!("optimum:  {} (gap {:.2}%)\n", optimum, gap));
+    }
+
+    summary.push_str(&format!("time:     {:.3}s\n", elapsed.as_secs_f64()));
+
+    match arguments.output {
+        Some(ref output) => {
+            let format = format_of(&arguments.output_format, output)?;
+            let contents = write_tour(&problem, &tour, &format, elapsed.as_millis() as u64)?;
+
+            if output == "-" {
+                eprint!("{}", summary);
+                io::stdout()
+                    .write_all(&contents)
+                    .map_err(|error| format!("cannot write the tour: {}", error))?;
+            } else {
+                fs::write(output, contents)
+                    .map_err(|error| format!("cannot write \"{}\": {}", output, error))?;
+                print!("{}", summary);
+            }
+        }
+        None => {
+            let route: Vec<String> = tour.route.iter().map(|city| city.to_string()).collect();
+            println!("{}route:    {}", summary, route.join(" "));
+        }
+    }
+
+    Ok(())
+}
+
+fn read_problem(input: &str, arguments: &Arguments) -> Result<Problem, String> {
+    let mut text = String::new();
+
+    let result = if input == "-" {
+        io::stdin().read_to_string(&mut text).map(|_| ())
+    } else {
+        fs::read_to_string(input).map(|contents| text = contents)
+    };
+
+    result.map_err(|error| format!("cannot read \"{}\": {}", input, error))?;
+
+    let name = Path::new(input)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("tour")
+        .to_string();
+
+    let mut problem = Problem {
+        name,
+        cities: vec![],
+        geographic: false,
+        distances: vec![],
+        solver: Solver::SimulatedAnnealing,
+        runtime: Duration::seconds(1),
+        restart_probability: 0.01,
+        options: Options::default(),
+    };
+
+    match format_of(&arguments.format, input)?.as_str() {
+        "tsplib" => {
+            if arguments.metric.is_some() {
+                return Err("TSPLIB instances give their own distances".to_string());
+            }
+
+            let instance = tsplib::parse(&text).map_err(|error| error.to_string())?;
+            let matrix = instance
+                .distance_matrix()
+                .map_err(|error| error.to_string())?;
+
+            if !instance.name.is_empty() {
+                problem.name = instance.name.clone();
+            }
+
+            problem.geographic = instance.edge_weight_type == "GEO";
+            problem.cities = instance.coordinates;
+            problem.distances = (0..matrix.size())
+                .map(|from| matrix.row(from).iter().map(|&d| d as f64).collect())
+                .collect();
+        }
+        "csv" => {
+            let (cities, geographic) = parse_csv(&text)?;
+            let default_metric = if geographic { "haversine" } else { "euclidean" };
+            let metric = arguments.metric.as_deref().unwrap_or(default_metric);
+
+            problem.distances = distance_matrix(&cities, metric)?;
+            problem.geographic = geographic;
+            problem.cities = cities;
+        }
+        "json" => read_json(&text, arguments, &mut problem)?,
+        other => return Err(format!("unknown input format \"{}\"", other)),
+    }
+
+    Ok(problem)
+}
+
+#[cfg(feature = "serde")]
+fn read_json(text: &str, arguments: &Arguments, problem: &mut Problem) -> Result<(), String> {
+    use travelling_salesman::json::MetricName;
+
+    let json = travelling_salesman::json::Problem::from_json(text).map_err(|e| e.to_string())?;
+
+    // the solver names are the same as on the command line
+
+    let solver_name = serde_json::to_value(json.solver).map_err(|e| e.to_string())?;
+
+    match arguments.metric {
+        Some(ref metric) if !json.cities.is_empty() => {
+            problem.distances = distance_matrix(&json.cities, metric)?;
+            problem.geographic = metric == "haversine" || metric == "vincenty" || metric == "geo";
+        }
+        _ => {
+            problem.distances = json.distance_matrix().map_err(|e| e.to_string())?;
+            problem.geographic = !json.cities.is_empty()
+                && matches!(
+                    json.metric,
+                    MetricName::Haversine | MetricName::Vincenty | MetricName::Geo
+                );
+        }
+    }
+
+    problem.cities = json.cities;
+
+    problem.solver = Solver::from_name(solver_name.as_str().unwrap_or("simulated_annealing"))?;
+    problem.runtime = Duration::milliseconds(json.runtime_ms as i64);
+    problem.restart_probability = json.restart_probability;
+    problem.options = json.options;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "serde"))]
+fn read_json(_: &str, _: &Arguments, _: &mut Problem) -> Result<(), String> {
+    Err("JSON needs tsp-solve to be built with the serde feature".to_string())
+}
+
+/// Parses cities from CSV text, returning whether they are latitudes and longitudes
+///
+/// Cities are taken from `x` and `y` columns, from `lat` and `lon` columns, or from the first two
+/// columns when there is no header row.
+fn parse_csv(text: &str) -> Result<(Vec<(f64, f64)>, bool), String> {
+    let rows: Vec<Vec<&str>> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.split(',')
+                .map(|field| field.trim().trim_matches('"'))
+                .collect()
+        })
+        .collect();
+
+    let header = match rows.first() {
+        Some(header) => header,
+        None => return Ok((vec![], false)),
+    };
+
+    let (x, y, rows) = if header.iter().all(|field| field.parse::<f64>().is_ok()) {
+        (0, 1, &rows[..])
+    } else {
+        let column = |name: &str| {
+            header
+                .iter()
+                .position(|field| field.eq_ignore_ascii_case(name))
+        };
+
+        match (column("x"), column("y")) {
+            (Some(x), Some(y)) => (x, y, &rows[1..]),
+            _ => {
+                let cities = geographic::parse_csv(text).map_err(|error| error.to_string())?;
+                return Ok((cities, true));
+            }
+        }
+    };
+
+    let mut cities = vec![];
+
+    for (index, row) in rows.iter().enumerate() {
+        let field = |column: usize| {
+            row.get(column)
+                .and_then(|field| field.parse::<f64>().ok())
+                .ok_or_else(|| format!("invalid coordinates in row {}", index + 1))
+        };
+
+        cities.push((field(x)?, field(y)?));
+    }
+
+    Ok((cities, false))
+}
+
+fn distance_matrix(cities: &[(f64, f64)], metric: &str) -> Result<Vec<Vec<f64>>, String> {
+    Ok(match metric {
+        "euclidean" => get_distance_matrix_with_metric(cities, &Euclidean::new()),
+        "rounded_euclidean" => get_distance_matrix_with_metric(cities, &RoundedEuclidean::new()),
+        "ceiling_euclidean" => get_distance_matrix_with_metric(cities, &CeilingEuclidean::new()),
+        "manhattan" => get_distance_matrix_with_metric(cities, &Manhattan::new()),
+        "chebyshev" => get_distance_matrix_with_metric(cities, &Chebyshev::new()),
+        "haversine" => get_distance_matrix_with_metric(cities, &Haversine::kilometres()),
+        "vincenty" => get_distance_matrix_with_metric(cities, &Vincenty::kilometres()),
+        "geo" => get_distance_matrix_with_metric(cities, &Geo),
+        other => match other.strip_prefix("minkowski:").map(str::parse) {
+            Some(Ok(p)) => {
+                let metric = Minkowski::new(p).map_err(|error| error.to_string())?;
+                get_distance_matrix_with_metric(cities, &metric)
+            }
+            Some(Err(_)) => return Err(format!("invalid Minkowski order in \"{}\"", other)),
+            None => return Err(format!("unknown metric \"{}\"", other)),
+        },
+    })
+}
+
+/// Overrides the settings of the problem with those given on the command line
+fn apply_arguments(problem: &mut Problem, arguments: &Arguments) -> Result<(), String> {
+    if let Some(solver) = arguments.solver {
+        problem.solver = solver;
+    }
+
+    // brute force cannot stop early, so a time or iteration limit would be silently ignored
+
+    if problem.solver == Solver::BruteForce
+        && (arguments.seconds.is_some() || arguments.iterations.is_some())
+    {
+        return Err(
+            "brute_force tries every route, so takes neither --time nor --iterations".to_string(),
+        );
+    }
+
+    if let Some(seconds) = arguments.seconds {
+        problem.runtime = Duration::milliseconds((seconds * 1000.0) as i64);
+    }
+
+    if let Some(restart_probability) = arguments.restart_probability {
+        problem.restart_probability = restart_probability;
+    }
+
+    let options = &mut problem.options;
+
+    if let Some(iterations) = arguments.iterations {
+        options.iterations = Some(iterations);
+    }
+
+    if let Some(seed) = arguments.seed {
+        options.seed = Some(seed);
+    }
+
+    if let Some(objective) = arguments.objective {
+        options.objective = objective;
+    }
+
+    if let Some(start) = arguments.start {
+        options.start_city = Some(start);
+    }
+
+    let shape = match arguments.shape.as_deref() {
+        Some("cycle") => Some(Shape::Cycle),
+        Some("path") => Some(Shape::Path),
+        Some(other) => return Err(format!("unknown shape \"{}\"", other)),
+        None if arguments.end.is_some() => Some(Shape::Path),
+        None => None,
+    };
+
+    if let Some(shape) = shape {
+        options.shape = shape;
+    }
+
+    if let Some(end) = arguments.end {
+        match (options.shape, options.start_city) {
+            (Shape::Path, Some(start)) | (Shape::PathFrom(start), _) => {
+                options.shape = Shape::PathBetween(start, end)
+            }
+            (Shape::Cycle, _) => return Err("--end only applies to paths".to_string()),
+            _ => return Err("--end needs --start".to_string()),
+        }
+    }
+
+    Ok(())
+}
+
+fn solve(problem: &Problem) -> Result<Tour, Error> {
+    let distances = &problem.distances;
+    let runtime = problem.runtime;
+    let options = &problem.options;
+
+    match problem.solver {
+        Solver::BruteForce => brute_force::solve_with_options(distances, options),
+        Solver::HillClimbing => hill_climbing::solve_with_options(distances, runtime, options),
+        Solver::RandomRestarts => hill_climbing::random_restarts::solve_with_options(
+            distances,
+            runtime,
+            problem.restart_probability,
+            options,
+        ),
+        Solver::RandomSearch => random_search::solve_with_options(distances, runtime, options),
+        Solver::SimulatedAnnealing => {
+            simulated_annealing::solve_with_options(distances, runtime, options)
+        }
+    }
+}
+
+/// Returns the tour written in `format`
+fn write_tour(
+    problem: &Problem,
+    tour: &Tour,
+    format: &str,
+    runtime_ms: u64,
+) -> Result<Vec<u8>, String> {
+    // drawings are laid out with longitude across and latitude up
+
+    let drawn_cities = || -> Result<Vec<(f64, f64)>, String> {
+        if problem.cities.is_empty() {
+            return Err(format!("cannot draw {} without coordinates", format));
+        }
+
+        Ok(if problem.geographic {
+            problem
+                .cities
+                .iter()
+                .map(|&(lat, lon)| (lon, lat))
+                .collect()
+        } else {
+            problem.cities.clone()
+        })
+    };
+
+    match format {
+        "tsplib" => Ok(tsplib::format_tour(&problem.name, tour).into_bytes()),
+        "json" => write_json(problem, tour, runtime_ms),
+        "csv" => {
+            let mut text = if problem.cities.is_empty() {
+                "stop,city\n".to_string()
+            } else if problem.geographic {
+                "stop,city,lat,lon\n".to_string()
+            } else {
+                "stop,city,x,y\n".to_string()
+            };
+
+            for (stop, &city) in tour.route.iter().enumerate() {
+                match problem.cities.get(city) {
+                    Some(&(x, y)) => text.push_str(&format!("{},{},{},{}\n", stop, city, x, y)),
+                    None => text.push_str(&format!("{},{}\n", stop, city)),
+                }
+            }
+
+            Ok(text.into_bytes())
+        }
+        "svg" => render::to_svg(
+            &drawn_cities()?,
+            slice::from_ref(tour),
+            &render::Style::default(),
+        )
+        .map(String::into_bytes)
+        .map_err(|error| error.to_string()),
+        #[cfg(feature = "png")]
+        "png" => render::to_png(
+            &drawn_cities()?,
+            slice::from_ref(tour),
+            &render::Style::default(),
+        )
+        .map_err(|error| error.to_string()),
+        other => Err(format!("unknown output format \"{}\"", other)),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn write_json(problem: &Problem, tour: &Tour, runtime_ms: u64) -> Result<Vec<u8>, String> {
+    use travelling_salesman::json::{Solution, Solver as JsonSolver, Stats};
+
+    // brute force proves its route is the shortest
+
+    let bound = if problem.solver == Solver::BruteForce
+        && problem.options.objective == Objective::MinimiseDistance
+    {
+        Some(tour.distance)
+    } else {
+        None
+    };
+
+    let solution = Solution {
+        route: tour.route.clone(),
+        distance: tour.distance,
+        closed: tour.closed,
+        bound,
+        stats: Stats {
+            solver: match problem.solver {
+                Solver::BruteForce => JsonSolver::BruteForce,
+                Solver::HillClimbing => JsonSolver::HillClimbing,
+                Solver::RandomRestarts => JsonSolver::RandomRestarts,
+                Solver::RandomSearch => JsonSolver::RandomSearch,
+                Solver::SimulatedAnnealing => JsonSolver::SimulatedAnnealing,
+            },
+            cities: problem.distances.len(),
+            runtime_ms,
+        },
+    };
+
+    Ok((solution.to_json() + "\n").into_bytes())
+}
+
+#[cfg(not(feature = "serde"))]
+fn write_json(_: &Problem, _: &Tour, _: u64) -> Result<Vec<u8>, String> {
+    Err("JSON needs tsp-solve to be built with the serde feature".to_string())
+}
@@ -26,7 +26,13 @@
 use std::collections::HashSet;
 use std::iter::FromIterator;
 
-use super::{get_distance_matrix, get_route_distance, Tour};
+use super::edges::Edges;
+use super::matrix::Weight;
+use super::metrics::{Euclidean, Metric};
+use super::oracle::DistanceOracle;
+use super::{
+    get_distance_matrix_with_metric, get_route_distance, Error, Objective, Options, Shape, Tour,
+};
 
 /// Returns an exact solution to the Travelling Salesman Problem using Brute Force
 ///
@@ -59,61 +65,251 @@ use super::{get_distance_matrix, get_route_distance, Tour};
 ///}
 ///```
 pub fn solve(cities: &[(f64, f64)]) -> Tour {
+    solve_with_metric(cities, &Euclidean::new())
+}
+
+/// Returns an exact solution to the Travelling Salesman Problem using Brute Force, measuring
+/// distances between cities with the specified metric
+///
+///# Parameters and Return Type
+///
+/// `cities` is an array slice, containing the coordinates for each city.
+///
+/// `metric` is a `travelling_salesman::metrics::Metric`, specifying how to measure the distance
+/// between two cities.
+///
+/// Returns a `travelling_salesman::Tour` struct, representing the exact solution found.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::metrics::Geo;
+///
+///fn main() {
+///  let tour = travelling_salesman::brute_force::solve_with_metric(
+///    &[
+///       (38.24, 20.42),
+///       (39.57, 26.15),
+///       (40.56, 25.32),
+///       (36.26, 23.12),
+///       (33.48, 10.54),
+///    ],
+///    &Geo,
+///  );
+///
+///  println!("Tour distance: {}km, route: {:?}", tour.distance, tour.route);
+///}
+///```
+pub fn solve_with_metric<P, M: Metric<P>>(cities: &[P], metric: &M) -> Tour {
+    solve_with_oracle(&get_distance_matrix_with_metric(cities, metric))
+}
+
+/// Returns an exact solution to the Travelling Salesman Problem using Brute Force, querying
+/// distances between cities from the specified oracle
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix or
+/// `travelling_salesman::oracle::LazyDistances`.
+///
+/// Returns a `travelling_salesman::Tour` struct, representing the exact solution found, with a
+/// distance of the same type as the oracle's weights. Integer weights are compared exactly.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let tour = travelling_salesman::brute_force::solve_with_oracle(&vec![
+///    vec![0i64, 3, 4, 2],
+///    vec![3, 0, 1, 5],
+///    vec![4, 1, 0, 6],
+///    vec![2, 5, 6, 0],
+///  ]);
+///
+///  assert_eq!(tour.distance, 12);
+///
+///  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+///}
+///```
+pub fn solve_with_oracle<D: DistanceOracle + ?Sized>(distances: &D) -> Tour<D::Weight> {
+    solve_with_options(distances, &Options::default()).expect("default options are always valid")
+}
+
+/// Returns an exact solution to the Travelling Salesman Problem using Brute Force, searching for
+/// the kind of route described by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix or
+/// `travelling_salesman::oracle::LazyDistances`.
+///
+/// `options` is a `travelling_salesman::Options` struct, specifying the kind of route to search for,
+/// the city it must start from, what makes one route better than another, and any edges it must or
+/// must not travel along.
+///
+/// Returns a `travelling_salesman::Tour` struct, representing the exact solution found, or a
+/// `travelling_salesman::Error` if the options are invalid for these distances, or no route keeping
+/// the fixed and forbidden edges was found.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::{Options, Shape};
+///
+///fn main() {
+///  let path = travelling_salesman::brute_force::solve_with_options(
+///    &vec![
+///      vec![0i64, 3, 4, 2],
+///      vec![3, 0, 1, 5],
+///      vec![4, 1, 0, 6],
+///      vec![2, 5, 6, 0],
+///    ],
+///    &Options {
+///      shape: Shape::Path,
+///      ..Options::default()
+///    },
+///  )
+///  .unwrap();
+///
+///  assert_eq!(path.distance, 6);
+///  assert!(!path.closed);
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    options: &Options,
+) -> Result<Tour<D::Weight>, Error> {
+    options.validate(distances.size())?;
+
+    let edges = options.edges(distances.size())?;
+
     let mut smallest_tour = Tour {
-        distance: 0.0,
+        distance: D::Weight::zero(),
         route: vec![],
+        closed: options.shape == Shape::Cycle,
     };
 
-    if cities.is_empty() {
-        return smallest_tour;
-    }
+    // any city can start a cycle, but a free path must try every starting city
+
+    let (start_cities, last_city) = match options.shape() {
+        Shape::Cycle => (vec![options.start_city.unwrap_or(0)], None),
+        Shape::Path => ((0..distances.size()).collect(), None),
+        Shape::PathFrom(start) => (vec![start], None),
+        Shape::PathBetween(start, end) => (vec![start], Some(end)),
+    };
+
+    for start_city in start_cities
+        .into_iter()
+        .filter(|&city| city < distances.size())
+    {
+        let mut unvisited_cities =
+            HashSet::<usize>::from_iter((0..distances.size()).collect::<Vec<usize>>());
+        let current_route = vec![start_city];
+        unvisited_cities.remove(&start_city);
+
+        let last_city = match last_city {
+            Some(end_city) => {
+                unvisited_cities.remove(&end_city);
+                Some(end_city)
+            }
+            None if options.shape == Shape::Cycle => Some(start_city),
+            None => None,
+        };
 
-    let mut unvisited_cities =
-        HashSet::<usize>::from_iter((0..cities.len()).collect::<Vec<usize>>());
-    let current_route = vec![0];
-    unvisited_cities.remove(&0);
+        _brute_force(
+            distances,
+            options.objective,
+            &edges,
+            unvisited_cities,
+            current_route,
+            last_city,
+            &mut smallest_tour,
+        );
+    }
 
-    _brute_force(
-        &get_distance_matrix(cities),
-        unvisited_cities,
-        current_route,
-        &mut smallest_tour,
-    );
+    if smallest_tour.route.is_empty() && distances.size() > 0 {
+        return Err(Error::Infeasible(
+            "no route with every fixed edge and no forbidden edge exists".to_string(),
+        ));
+    }
 
-    smallest_tour
+    Ok(smallest_tour)
 }
 
-fn _brute_force(
-    distance_matrix: &[Vec<f64>],
+fn _brute_force<D: DistanceOracle + ?Sized>(
+    distance_matrix: &D,
+    objective: Objective,
+    edges: &Edges,
     unvisited_cities: HashSet<usize>,
     current_route: Vec<usize>,
-    smallest_tour: &mut Tour,
+    last_city: Option<usize>,
+    smallest_tour: &mut Tour<D::Weight>,
 ) {
-    for unvisited_city in &unvisited_cities {
-        let mut my_unvisited_cities = unvisited_cities.clone();
-        my_unvisited_cities.remove(unvisited_city);
+    if unvisited_cities.is_empty() {
+        let mut my_route = current_route;
 
-        let mut my_route = current_route.clone();
-        my_route.push(*unvisited_city);
+        if let Some(last_city) = last_city {
+            my_route.push(last_city);
+        }
+
+        if edges.violations(&my_route) > 0 {
+            return;
+        }
+
+        if smallest_tour.route.is_empty()
+            || objective.better(distance_matrix, &my_route, &smallest_tour.route)
+        {
+            smallest_tour.distance = get_route_distance(distance_matrix, &my_route);
+            smallest_tour.route = my_route;
+        }
 
-        if my_unvisited_cities.is_empty() {
-            let home_city = my_route[0];
-            my_route.push(home_city);
+        return;
+    }
 
-            let my_route_distance = get_route_distance(distance_matrix, &my_route);
+    let current_city = current_route[current_route.len() - 1];
 
-            if (smallest_tour.distance == 0.0) || (my_route_distance < smallest_tour.distance) {
-                smallest_tour.distance = my_route_distance;
-                smallest_tour.route = my_route;
-            }
+    // the start of a cycle can keep one of its fixed edges for the return to it
 
-            return;
+    let spare_fixed_edges = if current_route.len() == 1 && last_city == Some(current_city) {
+        1
+    } else {
+        0
+    };
+
+    for unvisited_city in &unvisited_cities {
+        if edges.is_forbidden(current_city, *unvisited_city) {
+            continue;
+        }
+
+        let skipped_fixed_edges = edges
+            .fixed(current_city)
+            .iter()
+            .filter(|&city| city != unvisited_city && unvisited_cities.contains(city))
+            .count();
+
+        if skipped_fixed_edges > spare_fixed_edges {
+            continue;
         }
 
+        let mut my_unvisited_cities = unvisited_cities.clone();
+        my_unvisited_cities.remove(unvisited_city);
+
+        let mut my_route = current_route.clone();
+        my_route.push(*unvisited_city);
+
         _brute_force(
             distance_matrix,
+            objective,
+            edges,
             my_unvisited_cities,
             my_route,
+            last_city,
             smallest_tour,
         );
     }
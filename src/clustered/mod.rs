@@ -0,0 +1,276 @@
+//! Find an approximate solution to the Clustered Travelling Salesman Problem
+//!
+//! The cities are partitioned into clusters, and the tour must visit every city of a cluster
+//! contiguously, before moving on to the next cluster. For example, a technician finishes every
+//! job on one site before driving to the next.
+//!
+//! Tours are searched using hill climbing, where each tweak either reorders the cities within a
+//! cluster, or reorders whole clusters, so every tour searched keeps the clusters contiguous.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (0.0, 0.0),
+//!    (10.0, 0.0),
+//!    (50.0, 0.0),
+//!    (5.0, 5.0),
+//!    (55.0, 5.0),
+//!  ];
+//!
+//!  let tour = travelling_salesman::clustered::solve(
+//!    &travelling_salesman::get_distance_matrix(&cities),
+//!    &[vec![0, 2], vec![1, 3, 4]],
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::search::{self, Metaheuristics};
+use super::{get_route_distance, validate_clusters, Error, Options, Tour};
+
+struct ClusteredSalesman<'a, D: DistanceOracle + ?Sized> {
+    distances: &'a D,
+    clusters: &'a [Vec<usize>],
+    rng: &'a mut StdRng,
+}
+
+/// The clusters in the order they are visited, each listing its cities in the order they are
+/// visited
+struct Candidate {
+    blocks: Vec<Vec<usize>>,
+}
+
+impl Candidate {
+    fn route(&self) -> Vec<usize> {
+        let mut route: Vec<usize> = self.blocks.iter().flatten().cloned().collect();
+
+        if let Some(&home_city) = route.first() {
+            route.push(home_city);
+        }
+
+        route
+    }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Metaheuristics<Candidate> for ClusteredSalesman<'a, D> {
+    fn clone_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        Candidate {
+            blocks: candidate.blocks.clone(),
+        }
+    }
+
+    fn generate_candidate(&mut self) -> Candidate {
+        let mut blocks = self.clusters.to_vec();
+        blocks.shuffle(&mut self.rng);
+
+        for block in &mut blocks {
+            block.shuffle(&mut self.rng);
+        }
+
+        Candidate { blocks }
+    }
+
+    fn rank_candidate(&mut self, candidate: &Candidate) -> f64 {
+        0.0 - get_route_distance(self.distances, &candidate.route()).to_f64()
+    }
+
+    fn tweak_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        let mut blocks = candidate.blocks.clone();
+        let index = self.rng.gen_range(0..blocks.len());
+
+        if blocks[index].len() > 1 && self.rng.gen::<bool>() {
+            // reverse the cities between two positions within a cluster
+
+            let block = &mut blocks[index];
+            let first = self.rng.gen_range(0..block.len());
+            let second = self.rng.gen_range(0..block.len());
+
+            if first < second {
+                block[first..=second].reverse();
+            } else {
+                block[second..=first].reverse();
+            }
+        } else if blocks.len() > 1 && self.rng.gen::<bool>() {
+            // reverse the clusters between two positions, along with the cities within them
+
+            let other = self.rng.gen_range(0..blocks.len());
+            let (start, end) = if index < other {
+                (index, other)
+            } else {
+                (other, index)
+            };
+
+            blocks[start..=end].reverse();
+
+            for block in &mut blocks[start..=end] {
+                block.reverse();
+            }
+        } else if blocks.len() > 1 {
+            // move a cluster to another position, in either direction
+
+            let mut block = blocks.remove(index);
+
+            if self.rng.gen::<bool>() {
+                block.reverse();
+            }
+
+            let position = self.rng.gen_range(0..=blocks.len());
+            blocks.insert(position, block);
+        }
+
+        Candidate { blocks }
+    }
+}
+
+/// Returns an approximate solution to the Clustered Travelling Salesman Problem
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `clusters` is an array slice, containing the cities of each cluster. Cities which are not in
+/// any cluster are visited as clusters of their own.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::Tour` struct, visiting every city. Returns
+/// `travelling_salesman::Error::InvalidOptions` if a cluster is empty, or a city is in more than
+/// one cluster.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = [
+///    (0.0, 0.0),
+///    (10.0, 0.0),
+///    (20.0, 0.0),
+///    (30.0, 0.0),
+///  ];
+///
+///  let tour = travelling_salesman::clustered::solve(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    &[vec![0, 2], vec![1, 3]],
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(tour.distance, 60.0);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    clusters: &[Vec<usize>],
+    runtime: Duration,
+) -> Result<Tour<D::Weight>, Error> {
+    solve_with_options(distances, clusters, runtime, &Options::default())
+}
+
+/// Returns an approximate solution to the Clustered Travelling Salesman Problem, seeded and
+/// limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `clusters` is an array slice, containing the cities of each cluster. Cities which are not in
+/// any cluster are visited as clusters of their own.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::Tour` struct, visiting every city. Returns
+/// `travelling_salesman::Error::InvalidOptions` if a cluster is empty, or a city is in more than
+/// one cluster.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(20, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let clusters = [vec![0, 1, 2, 3], vec![4, 5, 6], vec![7, 8, 9, 10, 11]];
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::clustered::solve_with_options(
+///      &distance_matrix,
+///      &clusters,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    clusters: &[Vec<usize>],
+    runtime: Duration,
+    options: &Options,
+) -> Result<Tour<D::Weight>, Error> {
+    options.validate_search_only()?;
+
+    let size = distances.size();
+
+    validate_clusters(clusters, size)?;
+
+    let mut clusters = clusters.to_vec();
+    let mut clustered = vec![false; size];
+
+    for &city in clusters.iter().flatten() {
+        clustered[city] = true;
+    }
+
+    for city in (0..size).filter(|&city| !clustered[city]) {
+        clusters.push(vec![city]);
+    }
+
+    let mut ctsp = ClusteredSalesman {
+        distances,
+        clusters: &clusters,
+        rng: &mut options.rng(),
+    };
+
+    let route = search::hill_climbing(&mut ctsp, &mut options.budget(runtime)).route();
+
+    Ok(Tour {
+        distance: get_route_distance(distances, &route),
+        route,
+        closed: true,
+    })
+}
@@ -0,0 +1,367 @@
+//! Re-optimise an existing tour as cities are added, removed or moved
+//!
+//! During the day, new jobs arrive and others are cancelled. Rather than solving from scratch,
+//! the cities which changed are taken off the tour, the remaining cities keep their order, and
+//! the new and moved cities are added by cheapest insertion. The tour is then improved by a short
+//! local search of relocations, swaps and reversals.
+//!
+//! A prefix of the route, such as the stops already visited, can be frozen, so that it is kept
+//! exactly as it was.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::dynamic::Change;
+//!
+//!fn main() {
+//!  let mut cities = vec![
+//!    (27.0, 78.0),
+//!    (18.0, 24.0),
+//!    (48.0, 62.0),
+//!    (83.0, 77.0),
+//!    (55.0, 56.0),
+//!  ];
+//!
+//!  let mut tour = travelling_salesman::simulated_annealing::solve(&cities, time::Duration::seconds(1));
+//!  tour.rotate_to(0);
+//!
+//!  // the salesman has left city 0 when a new job arrives at city 5, and the job at city 4 moves
+//!
+//!  cities.push((60.0, 20.0));
+//!  cities[4] = (70.0, 40.0);
+//!
+//!  let tour = travelling_salesman::dynamic::solve(
+//!    &travelling_salesman::get_distance_matrix(&cities),
+//!    &tour,
+//!    &[Change::Insert(5), Change::Move(4)],
+//!    1,
+//!    time::Duration::milliseconds(100),
+//!  )
+//!  .unwrap();
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+use time::Duration;
+
+use super::matrix::Weight;
+use super::moves::random_move;
+use super::oracle::DistanceOracle;
+use super::{get_route_distance, validate_city, Error, Options, Tour};
+
+/// A change to the cities of a tour
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Change {
+    /// the city is added to the tour
+    Insert(usize),
+    /// the city is taken off the tour
+    Remove(usize),
+    /// the city is still on the tour, but its distances have changed, such as when its coordinates
+    /// move, so it is taken off and added again
+    Move(usize),
+}
+
+/// Returns an existing tour, re-optimised after some of its cities have changed
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix,
+/// containing the current distances between every city, including those inserted.
+///
+/// `tour` is the `travelling_salesman::Tour` struct before the changes. Its distance is ignored.
+///
+/// `changes` is an array slice of `travelling_salesman::dynamic::Change`s to the cities.
+///
+/// `frozen` is the number of cities at the start of the route which must stay exactly where they
+/// are, such as the stops already visited. The first city of a closed tour is always kept first.
+///
+/// `runtime` is a `time::Duration`, specifying the most time to spend improving the tour.
+///
+/// Returns a `travelling_salesman::Tour` struct, which is closed if the original tour was.
+/// Returns `travelling_salesman::Error::InvalidOptions` if a city is inserted which is already on
+/// the tour, a city is removed or moved which is not on the tour, or a frozen city is changed.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::dynamic::Change;
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let cities = [
+///    (0.0, 0.0),
+///    (10.0, 0.0),
+///    (10.0, 10.0),
+///    (0.0, 10.0),
+///    (5.0, 0.0),
+///  ];
+///
+///  let tour = Tour {
+///    distance: 40.0,
+///    route: vec![0, 1, 2, 3, 0],
+///    closed: true,
+///  };
+///
+///  // cities 0 and 1 have been visited, the job at city 2 is cancelled and one at city 4 is added
+///
+///  let tour = travelling_salesman::dynamic::solve(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    &tour,
+///    &[Change::Remove(2), Change::Insert(4)],
+///    2,
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(tour.route, vec![0, 1, 4, 3, 0]);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized, W>(
+    distances: &D,
+    tour: &Tour<W>,
+    changes: &[Change],
+    frozen: usize,
+    runtime: Duration,
+) -> Result<Tour<D::Weight>, Error> {
+    solve_with_options(
+        distances,
+        tour,
+        changes,
+        frozen,
+        runtime,
+        &Options::default(),
+    )
+}
+
+/// Returns an existing tour, re-optimised after some of its cities have changed, seeded and
+/// limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix,
+/// containing the current distances between every city, including those inserted.
+///
+/// `tour` is the `travelling_salesman::Tour` struct before the changes. Its distance is ignored.
+///
+/// `changes` is an array slice of `travelling_salesman::dynamic::Change`s to the cities.
+///
+/// `frozen` is the number of cities at the start of the route which must stay exactly where they
+/// are, such as the stops already visited. The first city of a closed tour is always kept first.
+///
+/// `runtime` is a `time::Duration`, specifying the most time to spend improving the tour, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::Tour` struct, which is closed if the original tour was.
+/// Returns `travelling_salesman::Error::InvalidOptions` if a city is inserted which is already on
+/// the tour, a city is removed or moved which is not on the tour, or a frozen city is changed.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::dynamic::Change;
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(20, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let tour = travelling_salesman::generators::circle_tour(&cities);
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::dynamic::solve_with_options(
+///      &distance_matrix,
+///      &tour,
+///      &[Change::Move(5), Change::Remove(9)],
+///      3,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized, W>(
+    distances: &D,
+    tour: &Tour<W>,
+    changes: &[Change],
+    frozen: usize,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Tour<D::Weight>, Error> {
+    options.validate_search_only()?;
+
+    let size = distances.size();
+    let closed = tour.closed;
+
+    let mut stops = tour.route.clone();
+
+    if closed && stops.len() > 1 {
+        stops.pop();
+    }
+
+    for &city in &stops {
+        validate_city(city, size)?;
+    }
+
+    if frozen > stops.len() {
+        return Err(Error::InvalidOptions(format!(
+            "cannot freeze {} cities of a route with {}",
+            frozen,
+            stops.len()
+        )));
+    }
+
+    let mut frozen = frozen;
+
+    if closed {
+        frozen = frozen.max(1).min(stops.len());
+    }
+
+    // take the changed cities off the tour
+
+    let mut pending = vec![];
+
+    for &change in changes {
+        match change {
+            Change::Insert(city) => {
+                validate_city(city, size)?;
+
+                if stops.contains(&city) || pending.contains(&city) {
+                    return Err(Error::InvalidOptions(format!(
+                        "city {} is already on the tour",
+                        city
+                    )));
+                }
+
+                pending.push(city);
+            }
+            Change::Remove(city) | Change::Move(city) => {
+                let position = match stops.iter().position(|&stop| stop == city) {
+                    Some(position) => position,
+                    None => {
+                        return Err(Error::InvalidOptions(format!(
+                            "city {} is not on the tour",
+                            city
+                        )))
+                    }
+                };
+
+                if position < frozen {
+                    return Err(Error::InvalidOptions(format!("city {} is frozen", city)));
+                }
+
+                stops.remove(position);
+
+                if let Change::Move(city) = change {
+                    pending.push(city);
+                }
+            }
+        }
+    }
+
+    // add the cheapest city to insert at each step, at its cheapest position after the frozen
+    // prefix
+
+    let distance = |from: usize, to: usize| distances.distance(from, to).to_f64();
+
+    while !pending.is_empty() {
+        let mut cheapest = (f64::INFINITY, 0, stops.len());
+
+        for (index, &city) in pending.iter().enumerate() {
+            for position in frozen..=stops.len() {
+                let previous = position.checked_sub(1).map(|p| stops[p]);
+                let next = if position < stops.len() {
+                    Some(stops[position])
+                } else if closed {
+                    stops.first().cloned()
+                } else {
+                    None
+                };
+
+                let cost = match (previous, next) {
+                    (Some(previous), Some(next)) => {
+                        distance(previous, city) + distance(city, next) - distance(previous, next)
+                    }
+                    (Some(previous), None) => distance(previous, city),
+                    (None, Some(next)) => distance(city, next),
+                    (None, None) => 0.0,
+                };
+
+                if cost < cheapest.0 {
+                    cheapest = (cost, index, position);
+                }
+            }
+        }
+
+        let (_, index, position) = cheapest;
+        stops.insert(position, pending.swap_remove(index));
+    }
+
+    // improve the cities after the frozen prefix, until no move has helped for a while
+
+    let route_of = |stops: &[usize]| {
+        let mut route = stops.to_vec();
+
+        if closed {
+            if let Some(&home_city) = stops.first() {
+                route.push(home_city);
+            }
+        }
+
+        route
+    };
+
+    let length = |stops: &[usize]| get_route_distance(distances, &route_of(stops)).to_f64();
+
+    let mut current = length(&stops);
+    let mut rng = options.rng();
+    let mut budget = options.budget(runtime);
+    let mut stale_iterations = 0;
+    let free = stops.len() - frozen;
+
+    while free >= 2 && stale_iterations <= 50 * free && budget.next() {
+        let (_, next_free) = random_move(&mut rng, &stops[frozen..]);
+        let mut next = stops[..frozen].to_vec();
+        next.extend(next_free);
+
+        let next_length = length(&next);
+
+        if next_length < current {
+            stops = next;
+            current = next_length;
+            stale_iterations = 0;
+        } else {
+            stale_iterations += 1;
+        }
+    }
+
+    let route = route_of(&stops);
+
+    Ok(Tour {
+        distance: get_route_distance(distances, &route),
+        route,
+        closed,
+    })
+}
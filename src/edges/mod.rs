@@ -0,0 +1,260 @@
+//! Fixed and forbidden edges, shared by the solvers which honour them
+//!
+//! An edge joins two cities in either direction. Fixed edges link cities into chains, which are
+//! kept together while routes are built, and moves which would lose a fixed edge or add a forbidden
+//! one are rejected.
+//!
+use std::collections::HashSet;
+
+use super::{validate_city, Error, Shape};
+
+/// The fixed and forbidden edges of a problem, checked to be consistent
+pub struct Edges {
+    /// the cities joined to each city by a fixed edge
+    fixed: Vec<Vec<usize>>,
+    fixed_count: usize,
+    forbidden: HashSet<(usize, usize)>,
+}
+
+/// Returns an edge with its cities in ascending order
+fn normalise(from: usize, to: usize) -> (usize, usize) {
+    if from < to {
+        (from, to)
+    } else {
+        (to, from)
+    }
+}
+
+impl Edges {
+    /// Returns the fixed and forbidden edges of a problem with `size` cities, searching for a
+    /// route of `shape`
+    ///
+    /// Returns `Error::Infeasible` if no route of that shape could contain every fixed edge while
+    /// avoiding every forbidden edge.
+    pub fn new(
+        size: usize,
+        shape: Shape,
+        fixed_edges: &[(usize, usize)],
+        forbidden_edges: &[(usize, usize)],
+    ) -> Result<Edges, Error> {
+        for &(from, to) in fixed_edges.iter().chain(forbidden_edges) {
+            validate_city(from, size)?;
+            validate_city(to, size)?;
+
+            if from == to {
+                return Err(Error::InvalidOptions(format!(
+                    "edge from city {} to itself",
+                    from
+                )));
+            }
+        }
+
+        let forbidden: HashSet<(usize, usize)> = forbidden_edges
+            .iter()
+            .map(|&(from, to)| normalise(from, to))
+            .collect();
+
+        let mut fixed = vec![vec![]; size];
+        let mut fixed_count = 0;
+        let mut components = Components::new(size);
+
+        for &(from, to) in fixed_edges {
+            if forbidden.contains(&normalise(from, to)) {
+                return Err(Error::Infeasible(format!(
+                    "the edge between cities {} and {} is both fixed and forbidden",
+                    from, to
+                )));
+            }
+
+            if fixed[from].contains(&to) {
+                continue;
+            }
+
+            for &city in &[from, to] {
+                if fixed[city].len() == 2 {
+                    return Err(Error::Infeasible(format!(
+                        "city {} has more than two fixed edges",
+                        city
+                    )));
+                }
+            }
+
+            // a cycle of fixed edges must be the whole tour
+
+            if !components.join(from, to) && (shape != Shape::Cycle || components.size(from) < size)
+            {
+                return Err(Error::Infeasible(format!(
+                    "the fixed edge between cities {} and {} closes a cycle",
+                    from, to
+                )));
+            }
+
+            fixed[from].push(to);
+            fixed[to].push(from);
+            fixed_count += 1;
+        }
+
+        let endpoints = match shape {
+            Shape::Cycle | Shape::Path => vec![],
+            Shape::PathFrom(start) => vec![start],
+            Shape::PathBetween(start, end) => {
+                if components.find(start) == components.find(end) && components.size(start) < size {
+                    return Err(Error::Infeasible(format!(
+                        "the fixed edges join cities {} and {}, which must end the path",
+                        start, end
+                    )));
+                }
+
+                vec![start, end]
+            }
+        };
+
+        for city in endpoints {
+            if fixed[city].len() > 1 {
+                return Err(Error::Infeasible(format!(
+                    "city {} ends the path, but has two fixed edges",
+                    city
+                )));
+            }
+        }
+
+        Ok(Edges {
+            fixed,
+            fixed_count,
+            forbidden,
+        })
+    }
+
+    /// Returns whether there are no fixed or forbidden edges
+    pub fn is_empty(&self) -> bool {
+        self.fixed_count == 0 && self.forbidden.is_empty()
+    }
+
+    /// Returns the cities joined to `city` by a fixed edge
+    pub fn fixed(&self, city: usize) -> &[usize] {
+        &self.fixed[city]
+    }
+
+    pub fn is_fixed(&self, from: usize, to: usize) -> bool {
+        self.fixed[from].contains(&to)
+    }
+
+    pub fn is_forbidden(&self, from: usize, to: usize) -> bool {
+        self.forbidden.contains(&normalise(from, to))
+    }
+
+    /// Returns the number of fixed edges missing from `route`, plus the number of forbidden edges
+    /// it travels along
+    pub fn violations(&self, route: &[usize]) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+
+        let mut present = 0;
+        let mut forbidden = 0;
+
+        for pair in route.windows(2) {
+            if self.is_fixed(pair[0], pair[1]) {
+                present += 1;
+            }
+
+            if self.is_forbidden(pair[0], pair[1]) {
+                forbidden += 1;
+            }
+        }
+
+        // a tour of two cities travels its only edge twice
+
+        self.fixed_count - present.min(self.fixed_count) + forbidden
+    }
+
+    /// Returns `Error::Infeasible` unless `route` contains every fixed edge and no forbidden edge
+    pub fn check(&self, route: &[usize]) -> Result<(), Error> {
+        if self.violations(route) == 0 {
+            Ok(())
+        } else {
+            Err(Error::Infeasible(
+                "no route with every fixed edge and no forbidden edge was found".to_string(),
+            ))
+        }
+    }
+
+    /// Returns the chains of cities linked by fixed edges, where cities without any are chains of
+    /// their own
+    pub fn chains(&self) -> Vec<Vec<usize>> {
+        let size = self.fixed.len();
+        let mut visited = vec![false; size];
+        let mut chains = vec![];
+
+        // start from the ends of chains, then from anywhere on a cycle of fixed edges
+
+        let starts = (0..size)
+            .filter(|&city| self.fixed[city].len() < 2)
+            .chain(0..size);
+
+        for start in starts {
+            if visited[start] {
+                continue;
+            }
+
+            let mut chain = vec![start];
+            visited[start] = true;
+
+            while let Some(&next) = self.fixed[chain[chain.len() - 1]]
+                .iter()
+                .find(|&&city| !visited[city])
+            {
+                visited[next] = true;
+                chain.push(next);
+            }
+
+            chains.push(chain);
+        }
+
+        chains
+    }
+}
+
+/// Disjoint sets of cities joined by fixed edges
+struct Components {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+}
+
+impl Components {
+    fn new(size: usize) -> Components {
+        Components {
+            parents: (0..size).collect(),
+            sizes: vec![1; size],
+        }
+    }
+
+    fn find(&mut self, city: usize) -> usize {
+        let mut root = city;
+
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+
+        self.parents[city] = root;
+        root
+    }
+
+    fn size(&mut self, city: usize) -> usize {
+        let root = self.find(city);
+        self.sizes[root]
+    }
+
+    /// Joins the components of `from` and `to`, returning false if they were already joined
+    fn join(&mut self, from: usize, to: usize) -> bool {
+        let (from, to) = (self.find(from), self.find(to));
+
+        if from == to {
+            return false;
+        }
+
+        self.parents[to] = from;
+        self.sizes[from] += self.sizes[to];
+        true
+    }
+}
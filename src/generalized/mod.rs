@@ -0,0 +1,337 @@
+//! Find an approximate solution to the Generalized Travelling Salesman Problem
+//!
+//! The cities are partitioned into clusters, and the tour must visit exactly one city from each
+//! cluster. For example, a delivery can be left at any parcel locker in a neighbourhood, or a pen
+//! plotter can draw each stroke in either direction, where each direction is a city whose distance
+//! from another stroke is measured from the end it finishes drawing at.
+//!
+//! The order of the clusters is searched using hill climbing. Whenever it changes, the best city
+//! from each cluster is chosen exactly, by finding the shortest cycle through the clusters in that
+//! order.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let lockers = [
+//!    (0.0, 0.0),
+//!    (50.0, 0.0),
+//!    (90.0, 0.0),
+//!    (10.0, 50.0),
+//!    (60.0, 50.0),
+//!  ];
+//!
+//!  let tour = travelling_salesman::generalized::solve(
+//!    &travelling_salesman::get_distance_matrix(&lockers),
+//!    &[vec![0, 1], vec![2], vec![3, 4]],
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::search::{self, Metaheuristics};
+use super::{get_route_distance, validate_clusters, Error, Options, Tour};
+
+struct GeneralizedSalesman<'a, D: DistanceOracle + ?Sized> {
+    distances: &'a D,
+    clusters: &'a [Vec<usize>],
+    rng: &'a mut StdRng,
+}
+
+struct Candidate {
+    order: Vec<usize>,
+    route: Vec<usize>,
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Metaheuristics<Candidate> for GeneralizedSalesman<'a, D> {
+    fn clone_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        Candidate {
+            order: candidate.order.clone(),
+            route: candidate.route.clone(),
+        }
+    }
+
+    fn generate_candidate(&mut self) -> Candidate {
+        let mut order: Vec<usize> = (0..self.clusters.len()).collect();
+        order.shuffle(&mut self.rng);
+
+        let route = self.choose_cities(&order);
+
+        Candidate { order, route }
+    }
+
+    fn rank_candidate(&mut self, candidate: &Candidate) -> f64 {
+        0.0 - get_route_distance(self.distances, &candidate.route).to_f64()
+    }
+
+    fn tweak_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        let mut order = candidate.order.clone();
+        let size = order.len();
+
+        if size < 3 {
+            return self.clone_candidate(candidate);
+        }
+
+        let first = self.rng.gen_range(0..size);
+        let second = self.rng.gen_range(0..size);
+
+        if self.rng.gen::<bool>() {
+            // reverse the clusters between two positions
+
+            let (start, end) = if first < second {
+                (first, second)
+            } else {
+                (second, first)
+            };
+
+            order[start..=end].reverse();
+        } else {
+            // move a cluster to another position
+
+            let cluster = order.remove(first);
+            order.insert(second, cluster);
+        }
+
+        let route = self.choose_cities(&order);
+
+        Candidate { order, route }
+    }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> GeneralizedSalesman<'a, D> {
+    fn distance(&self, from: usize, to: usize) -> f64 {
+        self.distances.distance(from, to).to_f64()
+    }
+
+    /// Returns the shortest closed route visiting one city from each cluster in `order`
+    ///
+    /// Starting from each city of the smallest cluster in turn, the shortest distance to every
+    /// city of each following cluster is found from the distances to the cluster before it.
+    fn choose_cities(&self, order: &[usize]) -> Vec<usize> {
+        let smallest = (0..order.len())
+            .min_by_key(|&position| self.clusters[order[position]].len())
+            .unwrap_or(0);
+        let order: Vec<usize> = order[smallest..]
+            .iter()
+            .chain(&order[..smallest])
+            .cloned()
+            .collect();
+
+        let mut shortest: Option<(f64, Vec<usize>)> = None;
+
+        for &start in &self.clusters[order[0]] {
+            // each layer holds, for every city of a cluster, the shortest distance from the start
+            // and the position of the city before it in the previous cluster
+
+            let mut layers: Vec<Vec<(f64, usize)>> = vec![vec![(0.0, 0)]];
+            let mut previous_cities: &[usize] = &[start];
+
+            for &cluster in &order[1..] {
+                let previous_layer = &layers[layers.len() - 1];
+
+                let layer = self.clusters[cluster]
+                    .iter()
+                    .map(|&city| {
+                        previous_cities
+                            .iter()
+                            .enumerate()
+                            .map(|(position, &previous)| {
+                                (
+                                    previous_layer[position].0 + self.distance(previous, city),
+                                    position,
+                                )
+                            })
+                            .fold((f64::INFINITY, 0), |best, next| {
+                                if next.0 < best.0 {
+                                    next
+                                } else {
+                                    best
+                                }
+                            })
+                    })
+                    .collect();
+
+                layers.push(layer);
+                previous_cities = &self.clusters[cluster];
+            }
+
+            let last_layer = &layers[layers.len() - 1];
+
+            let (distance, mut position) = previous_cities
+                .iter()
+                .enumerate()
+                .map(|(position, &previous)| {
+                    (
+                        last_layer[position].0 + self.distance(previous, start),
+                        position,
+                    )
+                })
+                .fold((f64::INFINITY, 0), |best, next| {
+                    if next.0 < best.0 {
+                        next
+                    } else {
+                        best
+                    }
+                });
+
+            if shortest
+                .as_ref()
+                .is_some_and(|&(shortest_distance, _)| shortest_distance <= distance)
+            {
+                continue;
+            }
+
+            let mut route = vec![start];
+
+            for layer in (1..layers.len()).rev() {
+                route.push(self.clusters[order[layer]][position]);
+                position = layers[layer][position].1;
+            }
+
+            route.push(start);
+            route.reverse();
+
+            shortest = Some((distance, route));
+        }
+
+        shortest.map(|(_, route)| route).unwrap_or_default()
+    }
+}
+
+/// Returns an approximate solution to the Generalized Travelling Salesman Problem
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `clusters` is an array slice, containing the cities of each cluster. Cities which are not in
+/// any cluster are never visited.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::Tour` struct, visiting one city from each cluster. Returns
+/// `travelling_salesman::Error::InvalidOptions` if a cluster is empty, or a city is in more than
+/// one cluster.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = [
+///    (0.0, 0.0),
+///    (100.0, 0.0),
+///    (0.0, 10.0),
+///    (100.0, 10.0),
+///  ];
+///
+///  let tour = travelling_salesman::generalized::solve(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    &[vec![0, 1], vec![2, 3]],
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(tour.distance, 20.0);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    clusters: &[Vec<usize>],
+    runtime: Duration,
+) -> Result<Tour<D::Weight>, Error> {
+    solve_with_options(distances, clusters, runtime, &Options::default())
+}
+
+/// Returns an approximate solution to the Generalized Travelling Salesman Problem, seeded and
+/// limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `clusters` is an array slice, containing the cities of each cluster. Cities which are not in
+/// any cluster are never visited.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::Tour` struct, visiting one city from each cluster. Returns
+/// `travelling_salesman::Error::InvalidOptions` if a cluster is empty, or a city is in more than
+/// one cluster.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(12, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let clusters = [vec![0, 1, 2], vec![3, 4, 5], vec![6, 7, 8], vec![9, 10, 11]];
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(100),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::generalized::solve_with_options(
+///      &distance_matrix,
+///      &clusters,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    clusters: &[Vec<usize>],
+    runtime: Duration,
+    options: &Options,
+) -> Result<Tour<D::Weight>, Error> {
+    options.validate_search_only()?;
+
+    validate_clusters(clusters, distances.size())?;
+
+    let mut gtsp = GeneralizedSalesman {
+        distances,
+        clusters,
+        rng: &mut options.rng(),
+    };
+
+    let best_candidate = search::hill_climbing(&mut gtsp, &mut options.budget(runtime));
+
+    Ok(Tour {
+        distance: get_route_distance(distances, &best_candidate.route),
+        route: best_candidate.route,
+        closed: true,
+    })
+}
@@ -0,0 +1,324 @@
+//! Generate random problem instances for testing and benchmarking
+//!
+//! Every random generator takes a seed, so that the same seed always gives the same instance with
+//! this version of the crate. Cities are `(x, y)` tuples for the Euclidean metric.
+//!
+//! Points on a circle and cities on a grid have known optimal tours, given by `circle_tour` and
+//! `grid_tour`, which makes them useful for checking how close a solver gets to the optimum.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::generators;
+//!
+//!fn main() {
+//!  let cities = generators::circle(20, 100.0, 42);
+//!  let optimum = generators::circle_tour(&cities);
+//!
+//!  let tour = travelling_salesman::simulated_annealing::solve(&cities, time::Duration::seconds(1));
+//!
+//!  println!(
+//!    "Tour distance: {}, gap to the optimum: {:.2}%",
+//!    tour.distance,
+//!    (tour.distance - optimum.distance) / optimum.distance * 100.0,
+//!  );
+//!}
+//!```
+//!
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+
+use super::{get_distance_matrix, get_route_distance, Tour};
+
+/// Returns `size` cities spread uniformly at random over a square with sides of length `side`
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(100, 1000.0, 7);
+///
+///  assert_eq!(cities.len(), 100);
+///  assert!(cities.iter().all(|&(x, y)| (0.0..1000.0).contains(&x) && (0.0..1000.0).contains(&y)));
+///  assert_eq!(cities, travelling_salesman::generators::uniform(100, 1000.0, 7));
+///}
+///```
+pub fn uniform(size: usize, side: f64, seed: u64) -> Vec<(f64, f64)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..size)
+        .map(|_| (rng.gen::<f64>() * side, rng.gen::<f64>() * side))
+        .collect()
+}
+
+/// Returns `size` cities gathered in clusters, as in the DIMACS TSP Challenge
+///
+/// The centres of the clusters are spread uniformly at random over a square with sides of length
+/// `side`. Each city belongs to a random cluster, and is offset from its centre in each direction
+/// by a normally distributed distance with a standard deviation of `side / sqrt(size)`. The
+/// DIMACS instances use `size / 10` clusters. At least one cluster is always used.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::clustered(1000, 100, 1_000_000.0, 7);
+///
+///  assert_eq!(cities.len(), 1000);
+///}
+///```
+pub fn clustered(size: usize, clusters: usize, side: f64, seed: u64) -> Vec<(f64, f64)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let centres: Vec<(f64, f64)> = (0..clusters.max(1))
+        .map(|_| (rng.gen::<f64>() * side, rng.gen::<f64>() * side))
+        .collect();
+
+    let deviation = side / (size.max(1) as f64).sqrt();
+
+    (0..size)
+        .map(|_| {
+            let (x, y) = centres[rng.gen_range(0..centres.len())];
+
+            (
+                x + gaussian(&mut rng) * deviation,
+                y + gaussian(&mut rng) * deviation,
+            )
+        })
+        .collect()
+}
+
+/// Returns a normally distributed number with a mean of zero and a standard deviation of one,
+/// using the Box-Muller transform
+fn gaussian(rng: &mut StdRng) -> f64 {
+    let u = 1.0 - rng.gen::<f64>();
+    let v = rng.gen::<f64>();
+
+    (-2.0 * u.ln()).sqrt() * (2.0 * PI * v).cos()
+}
+
+/// Returns the cities of a grid with `rows` rows and `columns` columns, `spacing` apart
+///
+/// The city in row `r` and column `c` is `(c * spacing, r * spacing)`, numbered
+/// `r * columns + c`.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::grid(2, 3, 10.0);
+///
+///  assert_eq!(
+///    cities,
+///    vec![(0.0, 0.0), (10.0, 0.0), (20.0, 0.0), (0.0, 10.0), (10.0, 10.0), (20.0, 10.0)],
+///  );
+///}
+///```
+pub fn grid(rows: usize, columns: usize, spacing: f64) -> Vec<(f64, f64)> {
+    (0..rows)
+        .flat_map(|row| {
+            (0..columns).map(move |column| (column as f64 * spacing, row as f64 * spacing))
+        })
+        .collect()
+}
+
+/// Returns the cities of a grid, each moved by up to `jitter` in each direction at random
+///
+/// Cities are numbered as in `grid`. While `jitter` is small compared to `spacing`, the optimal
+/// tour usually stays close to that of the grid.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::perturbed_grid(10, 10, 100.0, 5.0, 7);
+///  let grid = travelling_salesman::generators::grid(10, 10, 100.0);
+///
+///  for (city, point) in cities.iter().zip(&grid) {
+///    assert!((city.0 - point.0).abs() <= 5.0 && (city.1 - point.1).abs() <= 5.0);
+///  }
+///}
+///```
+pub fn perturbed_grid(
+    rows: usize,
+    columns: usize,
+    spacing: f64,
+    jitter: f64,
+    seed: u64,
+) -> Vec<(f64, f64)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    grid(rows, columns, spacing)
+        .into_iter()
+        .map(|(x, y)| {
+            (
+                x + (rng.gen::<f64>() * 2.0 - 1.0) * jitter,
+                y + (rng.gen::<f64>() * 2.0 - 1.0) * jitter,
+            )
+        })
+        .collect()
+}
+
+/// Returns an optimal tour of the cities of `grid`, if one is known
+///
+/// When there are at least two rows and two columns, and an even number of cities, the tour
+/// snakes along the rows and returns down the first column, travelling only between neighbouring
+/// cities, so its distance is `rows * columns * spacing`. Returns `None` otherwise.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let tour = travelling_salesman::generators::grid_tour(2, 3, 10.0).unwrap();
+///
+///  assert_eq!(tour.route, vec![0, 1, 2, 5, 4, 3, 0]);
+///  assert_eq!(tour.distance, 60.0);
+///
+///  assert!(travelling_salesman::generators::grid_tour(3, 3, 10.0).is_none());
+///}
+///```
+pub fn grid_tour(rows: usize, columns: usize, spacing: f64) -> Option<Tour> {
+    if rows < 2 || columns < 2 || (rows * columns) % 2 != 0 {
+        return None;
+    }
+
+    // snake along an even number of lines, which are the rows unless there is an odd number of
+    // them
+
+    let by_rows = rows % 2 == 0;
+    let (lines, length) = if by_rows {
+        (rows, columns)
+    } else {
+        (columns, rows)
+    };
+
+    let city = |line: usize, position: usize| {
+        if by_rows {
+            line * columns + position
+        } else {
+            position * columns + line
+        }
+    };
+
+    let mut route: Vec<usize> = (0..length).map(|position| city(0, position)).collect();
+
+    for line in 1..lines {
+        if line % 2 == 1 {
+            route.extend((1..length).rev().map(|position| city(line, position)));
+        } else {
+            route.extend((1..length).map(|position| city(line, position)));
+        }
+    }
+
+    route.extend((0..lines).rev().map(|line| city(line, 0)));
+
+    Some(Tour {
+        distance: get_route_distance(&get_distance_matrix(&grid(rows, columns, spacing)), &route),
+        route,
+        closed: true,
+    })
+}
+
+/// Returns `size` cities at random angles around a circle of `radius`, centred on the origin
+///
+/// The cities are numbered in order of their angle, so visiting them in order is the optimal
+/// tour, as given by `circle_tour`.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::generators;
+///
+///fn main() {
+///  let cities = generators::circle(7, 50.0, 7);
+///
+///  let tour = travelling_salesman::brute_force::solve(&cities);
+///
+///  assert!((tour.distance - generators::circle_tour(&cities).distance).abs() < 1e-9);
+///}
+///```
+pub fn circle(size: usize, radius: f64, seed: u64) -> Vec<(f64, f64)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut angles: Vec<f64> = (0..size).map(|_| rng.gen::<f64>() * 2.0 * PI).collect();
+    angles.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    angles
+        .into_iter()
+        .map(|angle| (radius * angle.cos(), radius * angle.sin()))
+        .collect()
+}
+
+/// Returns the optimal tour of cities generated by `circle`, visiting them in order
+pub fn circle_tour(cities: &[(f64, f64)]) -> Tour {
+    let mut route: Vec<usize> = (0..cities.len()).collect();
+
+    if !cities.is_empty() {
+        route.push(0);
+    }
+
+    Tour {
+        distance: get_route_distance(&get_distance_matrix(cities), &route),
+        route,
+        closed: true,
+    }
+}
+
+/// Returns a random asymmetric distance matrix of `size` cities
+///
+/// The distance from each city to every other is chosen uniformly at random from `[0,
+/// max_distance)`, independently in each direction. The distance from each city to itself is
+/// zero.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let distances = travelling_salesman::generators::asymmetric(10, 100.0, 7);
+///
+///  assert_eq!(distances.len(), 10);
+///  assert_eq!(distances[3][3], 0.0);
+///  assert_ne!(distances[3][4], distances[4][3]);
+///
+///  let tour =
+///    travelling_salesman::hill_climbing::solve_with_oracle(&distances, time::Duration::milliseconds(100));
+///
+///  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+///}
+///```
+pub fn asymmetric(size: usize, max_distance: f64, seed: u64) -> Vec<Vec<f64>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    (0..size)
+        .map(|from| {
+            (0..size)
+                .map(|to| {
+                    if from == to {
+                        0.0
+                    } else {
+                        rng.gen::<f64>() * max_distance
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
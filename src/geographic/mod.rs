@@ -0,0 +1,522 @@
+//! Read and write geographic tours, for use with mapping tools
+//!
+//! Cities are `(latitude, longitude)` tuples in degrees, as measured by
+//! `travelling_salesman::metrics::Haversine`. They can be read from CSV files with latitude and
+//! longitude columns, or from GeoJSON point collections when the `serde` feature is enabled.
+//!
+//! Tours can be written as a GeoJSON `FeatureCollection`, a GPX route or a KML path. Stops are
+//! numbered from 1 in the order they are visited.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::metrics::Haversine;
+//!
+//!fn main() {
+//!  let cities = travelling_salesman::geographic::parse_csv(
+//!    "name,lat,lon
+//!     Sydney,-33.87,151.21
+//!     Melbourne,-37.81,144.96
+//!     Brisbane,-27.47,153.03
+//!     Adelaide,-34.93,138.60",
+//!  )
+//!  .unwrap();
+//!
+//!  let tour = travelling_salesman::simulated_annealing::solve_with_metric(
+//!    &cities,
+//!    &Haversine::kilometres(),
+//!    time::Duration::seconds(1),
+//!  );
+//!
+//!  let kml = travelling_salesman::geographic::to_kml(&cities, &tour).unwrap();
+//!
+//!  println!("{}", kml);
+//!}
+//!```
+//!
+use std::fs;
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde_json::Value;
+
+use super::matrix::Weight;
+use super::{validate_city, Error, Tour};
+
+/// Parses `(latitude, longitude)` cities from CSV text, with a header row naming the columns
+///
+/// The latitude column is named `lat` or `latitude`, and the longitude column `lon`, `lng`,
+/// `long` or `longitude`, in any case. Other columns are ignored. Fields may be quoted, but may
+/// not contain commas.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::geographic::parse_csv(
+///    "Longitude,Latitude
+///     151.21,-33.87
+///     144.96,-37.81",
+///  )
+///  .unwrap();
+///
+///  assert_eq!(cities, vec![(-33.87, 151.21), (-37.81, 144.96)]);
+///}
+///```
+pub fn parse_csv(text: &str) -> Result<Vec<(f64, f64)>, Error> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|&(_, line)| !line.is_empty());
+
+    let header: Vec<String> = match lines.next() {
+        Some((_, line)) => fields(line).map(|field| field.to_lowercase()).collect(),
+        None => return Ok(vec![]),
+    };
+
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|field| names.contains(&field.as_str()))
+    };
+
+    let (latitude, longitude) = match (
+        column(&["lat", "latitude"]),
+        column(&["lon", "lng", "long", "longitude"]),
+    ) {
+        (Some(latitude), Some(longitude)) => (latitude, longitude),
+        _ => {
+            return Err(Error::Parse(
+                "expected latitude and longitude columns in the header".to_string(),
+            ))
+        }
+    };
+
+    lines
+        .map(|(number, line)| {
+            let row: Vec<&str> = fields(line).collect();
+            let coordinate = |column: usize, name: &str| -> Result<f64, Error> {
+                let field = row.get(column).cloned().unwrap_or("");
+
+                field.parse().map_err(|_| {
+                    Error::Parse(format!("line {}: invalid {} \"{}\"", number, name, field))
+                })
+            };
+
+            Ok((
+                coordinate(latitude, "latitude")?,
+                coordinate(longitude, "longitude")?,
+            ))
+        })
+        .collect()
+}
+
+/// Returns the fields of a CSV line, without surrounding whitespace or quotes
+fn fields(line: &str) -> impl Iterator<Item = &str> {
+    line.split(',').map(|field| field.trim().trim_matches('"'))
+}
+
+/// Reads `(latitude, longitude)` cities from the CSV file at `path`, as described by `parse_csv`
+pub fn read_csv<P: AsRef<Path>>(path: P) -> Result<Vec<(f64, f64)>, Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse_csv(&text),
+        Err(error) => Err(Error::Io(error.to_string())),
+    }
+}
+
+/// Parses `(latitude, longitude)` cities from GeoJSON text
+///
+/// The text may be a `FeatureCollection` or a single `Feature` of `Point` or `MultiPoint`
+/// geometries, or one of those geometries on its own. Cities are in the order they appear.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::geographic::parse_geojson(
+///    r#"{
+///      "type": "FeatureCollection",
+///      "features": [
+///        {"type": "Feature", "geometry": {"type": "Point", "coordinates": [151.21, -33.87]}},
+///        {"type": "Feature", "geometry": {"type": "Point", "coordinates": [144.96, -37.81]}}
+///      ]
+///    }"#,
+///  )
+///  .unwrap();
+///
+///  assert_eq!(cities, vec![(-33.87, 151.21), (-37.81, 144.96)]);
+///}
+///```
+#[cfg(feature = "serde")]
+pub fn parse_geojson(text: &str) -> Result<Vec<(f64, f64)>, Error> {
+    let value: Value =
+        serde_json::from_str(text).map_err(|error| Error::Parse(error.to_string()))?;
+    let mut cities = vec![];
+
+    match value["type"].as_str() {
+        Some("FeatureCollection") => {
+            let features = value["features"].as_array().ok_or_else(|| {
+                Error::Parse("expected a list of features in the collection".to_string())
+            })?;
+
+            for feature in features {
+                geometry_points(&feature["geometry"], &mut cities)?;
+            }
+        }
+        Some("Feature") => geometry_points(&value["geometry"], &mut cities)?,
+        _ => geometry_points(&value, &mut cities)?,
+    }
+
+    Ok(cities)
+}
+
+/// Adds the points of a GeoJSON `Point` or `MultiPoint` geometry to `cities`
+#[cfg(feature = "serde")]
+fn geometry_points(geometry: &Value, cities: &mut Vec<(f64, f64)>) -> Result<(), Error> {
+    let position = |coordinates: &Value| match (coordinates[0].as_f64(), coordinates[1].as_f64()) {
+        (Some(longitude), Some(latitude)) => Ok((latitude, longitude)),
+        _ => Err(Error::Parse(format!(
+            "expected a [longitude, latitude] position, but found {}",
+            coordinates
+        ))),
+    };
+
+    match geometry["type"].as_str() {
+        Some("Point") => cities.push(position(&geometry["coordinates"])?),
+        Some("MultiPoint") => {
+            for coordinates in geometry["coordinates"].as_array().into_iter().flatten() {
+                cities.push(position(coordinates)?);
+            }
+        }
+        _ => {
+            return Err(Error::Parse(format!(
+                "expected a Point or MultiPoint geometry, but found {}",
+                geometry["type"]
+            )))
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads `(latitude, longitude)` cities from the GeoJSON file at `path`, as described by
+/// `parse_geojson`
+#[cfg(feature = "serde")]
+pub fn read_geojson<P: AsRef<Path>>(path: P) -> Result<Vec<(f64, f64)>, Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse_geojson(&text),
+        Err(error) => Err(Error::Io(error.to_string())),
+    }
+}
+
+/// A city, with its `(latitude, longitude)`
+type Stop = (usize, (f64, f64));
+
+/// Returns the stops of `tour` in visiting order
+///
+/// Returns `Error::InvalidCity` if the route visits a city without coordinates, or
+/// `Error::InvalidOptions` if its coordinates are not finite numbers.
+fn stops<W>(cities: &[(f64, f64)], tour: &Tour<W>) -> Result<Vec<Stop>, Error> {
+    tour.stops()
+        .iter()
+        .map(|&city| {
+            validate_city(city, cities.len())?;
+
+            let (latitude, longitude) = cities[city];
+
+            if !latitude.is_finite() || !longitude.is_finite() {
+                return Err(Error::InvalidOptions(format!(
+                    "city {} has coordinates ({}, {}), which are not finite",
+                    city, latitude, longitude
+                )));
+            }
+
+            Ok((city, cities[city]))
+        })
+        .collect()
+}
+
+/// Returns the stops along the line following `tour`, back to the first stop if it is closed
+fn line<W>(stops: &[Stop], tour: &Tour<W>) -> Vec<Stop> {
+    let mut line = stops.to_vec();
+
+    if tour.closed && !stops.is_empty() {
+        line.push(stops[0]);
+    }
+
+    line
+}
+
+/// A GeoJSON `FeatureCollection`
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct FeatureCollection {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    features: Vec<Feature>,
+}
+
+/// A GeoJSON `Feature`, either the route or one of its stops
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+struct Feature {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    geometry: Geometry,
+    properties: Properties,
+}
+
+/// A GeoJSON geometry, with `[longitude, latitude]` positions
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(tag = "type", content = "coordinates")]
+enum Geometry {
+    LineString(Vec<[f64; 2]>),
+    Point([f64; 2]),
+}
+
+/// The properties of a `Feature`, the distance of the route or the numbering of a stop
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Properties {
+    Route { distance: f64, closed: bool },
+    Stop { stop: usize, city: usize },
+}
+
+/// Returns the GeoJSON document for a tour with `stops`, travelled along `line`
+#[cfg(feature = "serde")]
+fn geojson(stops: &[Stop], line: &[Stop], distance: f64, closed: bool) -> String {
+    let position = |&(_, (latitude, longitude)): &Stop| [longitude, latitude];
+
+    let mut features = vec![Feature {
+        kind: "Feature",
+        geometry: Geometry::LineString(line.iter().map(position).collect()),
+        properties: Properties::Route { distance, closed },
+    }];
+
+    for (stop, &(city, coordinates)) in stops.iter().enumerate() {
+        features.push(Feature {
+            kind: "Feature",
+            geometry: Geometry::Point(position(&(city, coordinates))),
+            properties: Properties::Stop {
+                stop: stop + 1,
+                city,
+            },
+        });
+    }
+
+    let collection = FeatureCollection {
+        kind: "FeatureCollection",
+        features,
+    };
+
+    serde_json::to_string_pretty(&collection).expect("tours can always be written as JSON") + "\n"
+}
+
+/// Returns the GeoJSON document for a tour with `stops`, travelled along `line`, written by hand
+/// when `serde_json` is not available
+#[cfg(not(feature = "serde"))]
+fn geojson(stops: &[Stop], line: &[Stop], distance: f64, closed: bool) -> String {
+    let positions: Vec<String> = line
+        .iter()
+        .map(|&(_, (latitude, longitude))| format!("[{}, {}]", longitude, latitude))
+        .collect();
+
+    // JSON has no infinite numbers, so an unreachable distance is written as null, as serde does
+    let distance = if distance.is_finite() {
+        distance.to_string()
+    } else {
+        "null".to_string()
+    };
+
+    let mut features = vec![format!(
+        "    {{\"type\": \"Feature\", \"geometry\": {{\"type\": \"LineString\", \"coordinates\": [{}]}}, \"properties\": {{\"distance\": {}, \"closed\": {}}}}}",
+        positions.join(", "),
+        distance,
+        closed
+    )];
+
+    for (stop, &(city, (latitude, longitude))) in stops.iter().enumerate() {
+        features.push(format!(
+            "    {{\"type\": \"Feature\", \"geometry\": {{\"type\": \"Point\", \"coordinates\": [{}, {}]}}, \"properties\": {{\"stop\": {}, \"city\": {}}}}}",
+            longitude,
+            latitude,
+            stop + 1,
+            city
+        ));
+    }
+
+    format!(
+        "{{\n  \"type\": \"FeatureCollection\",\n  \"features\": [\n{}\n  ]\n}}\n",
+        features.join(",\n")
+    )
+}
+
+/// Returns `tour` as a GeoJSON `FeatureCollection`
+///
+/// The first feature is a `LineString` following the route, with the tour distance as a property.
+/// It is followed by a `Point` feature for each stop, with properties `stop`, numbering the stops
+/// in visiting order, and `city`, the index of the city. The document is written by `serde_json`
+/// when the `serde` feature is enabled.
+///
+/// Returns `travelling_salesman::Error::InvalidCity` if the route visits a city without
+/// coordinates, or `travelling_salesman::Error::InvalidOptions` if a city's coordinates are not
+/// finite.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let tour = Tour {
+///    distance: 713.0,
+///    route: vec![1, 0, 1],
+///    closed: true,
+///  };
+///
+///  let geojson =
+///    travelling_salesman::geographic::to_geojson(&[(-33.87, 151.21), (-37.81, 144.96)], &tour)
+///      .unwrap();
+///
+///  let compact: String = geojson.split_whitespace().collect();
+///
+///  assert!(compact.contains(r#""coordinates":[[144.96,-37.81],[151.21,-33.87],[144.96,-37.81]]"#));
+///  assert!(compact.contains(r#""properties":{"stop":2,"city":0}"#));
+///
+///  assert!(
+///    travelling_salesman::geographic::to_geojson(&[(-33.87, 151.21), (std::f64::NAN, 144.96)], &tour)
+///      .is_err()
+///  );
+///}
+///```
+pub fn to_geojson<W: Weight>(cities: &[(f64, f64)], tour: &Tour<W>) -> Result<String, Error> {
+    let stops = stops(cities, tour)?;
+    let line = line(&stops, tour);
+
+    Ok(geojson(&stops, &line, tour.distance.to_f64(), tour.closed))
+}
+
+/// Returns `tour` as a GPX route, with a route point for each stop named by its stop number and
+/// city
+///
+/// A closed tour finishes with a route point back at the first city.
+///
+/// Returns `travelling_salesman::Error::InvalidCity` if the route visits a city without
+/// coordinates, or `travelling_salesman::Error::InvalidOptions` if a city's coordinates are not
+/// finite.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let tour = Tour {
+///    distance: 713.0,
+///    route: vec![1, 0],
+///    closed: false,
+///  };
+///
+///  let gpx =
+///    travelling_salesman::geographic::to_gpx(&[(-33.87, 151.21), (-37.81, 144.96)], &tour).unwrap();
+///
+///  assert!(gpx.contains(r#"<rtept lat="-37.81" lon="144.96"><name>Stop 1: city 1</name></rtept>"#));
+///}
+///```
+pub fn to_gpx<W>(cities: &[(f64, f64)], tour: &Tour<W>) -> Result<String, Error> {
+    let stops = stops(cities, tour)?;
+    let mut gpx = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\" creator=\"travelling_salesman\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n  <rte>\n    <name>Tour</name>\n",
+    );
+
+    for (stop, &(city, (latitude, longitude))) in stops.iter().enumerate() {
+        gpx += &format!(
+            "    <rtept lat=\"{}\" lon=\"{}\"><name>Stop {}: city {}</name></rtept>\n",
+            latitude,
+            longitude,
+            stop + 1,
+            city
+        );
+    }
+
+    if let (true, Some(&(city, (latitude, longitude)))) = (tour.closed, stops.first()) {
+        gpx += &format!(
+            "    <rtept lat=\"{}\" lon=\"{}\"><name>Return: city {}</name></rtept>\n",
+            latitude, longitude, city
+        );
+    }
+
+    gpx += "  </rte>\n</gpx>\n";
+
+    Ok(gpx)
+}
+
+/// Returns `tour` as a KML document, with a placemark for each stop named by its stop number and
+/// city, followed by a path following the route
+///
+/// Returns `travelling_salesman::Error::InvalidCity` if the route visits a city without
+/// coordinates, or `travelling_salesman::Error::InvalidOptions` if a city's coordinates are not
+/// finite.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let tour = Tour {
+///    distance: 713.0,
+///    route: vec![1, 0, 1],
+///    closed: true,
+///  };
+///
+///  let kml =
+///    travelling_salesman::geographic::to_kml(&[(-33.87, 151.21), (-37.81, 144.96)], &tour).unwrap();
+///
+///  assert!(kml.contains("<coordinates>144.96,-37.81 151.21,-33.87 144.96,-37.81</coordinates>"));
+///}
+///```
+pub fn to_kml<W>(cities: &[(f64, f64)], tour: &Tour<W>) -> Result<String, Error> {
+    let stops = stops(cities, tour)?;
+    let mut kml = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<kml xmlns=\"http://www.opengis.net/kml/2.2\">\n  <Document>\n    <name>Tour</name>\n",
+    );
+
+    for (stop, &(city, (latitude, longitude))) in stops.iter().enumerate() {
+        kml += &format!(
+            "    <Placemark><name>Stop {}: city {}</name><Point><coordinates>{},{}</coordinates></Point></Placemark>\n",
+            stop + 1,
+            city,
+            longitude,
+            latitude
+        );
+    }
+
+    let positions: Vec<String> = line(&stops, tour)
+        .iter()
+        .map(|&(_, (latitude, longitude))| format!("{},{}", longitude, latitude))
+        .collect();
+
+    kml += &format!(
+        "    <Placemark><name>Route</name><LineString><coordinates>{}</coordinates></LineString></Placemark>\n  </Document>\n</kml>\n",
+        positions.join(" ")
+    );
+
+    Ok(kml)
+}
@@ -0,0 +1,643 @@
+//! Sparse graphs, where only some pairs of cities are joined by an edge
+//!
+//! A road network rarely joins every pair of cities directly. There are two ways to solve such a
+//! problem:
+//!
+//! * `Graph::shortest_paths` finds the shortest path between every pair of cities, giving a
+//!   complete distance matrix which any solver in this crate accepts. The tour found can then be
+//!   expanded back into the edges of the graph, passing through cities more than once if needed.
+//! * `solve_hamiltonian` searches only the edges of the graph for a tour visiting each city exactly
+//!   once, reporting when no such tour exists.
+//!
+//! Edge weights must not be negative.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::graph::Graph;
+//!
+//!fn main() {
+//!  // a star of roads, where every trip passes through city 0
+//!
+//!  let graph = Graph::from_edges(4, &[(0, 1, 2.0), (0, 2, 3.0), (0, 3, 4.0)]).unwrap();
+//!
+//!  let shortest_paths = graph.shortest_paths().unwrap();
+//!  let tour = travelling_salesman::simulated_annealing::solve_with_oracle(
+//!    &shortest_paths,
+//!    time::Duration::seconds(1),
+//!  );
+//!
+//!  let roads = shortest_paths.expand_tour(&tour);
+//!
+//!  assert_eq!(roads.distance, 18.0);
+//!  assert_eq!(roads.route.len(), 7);
+//!
+//!  // no tour visits every city exactly once
+//!
+//!  assert!(travelling_salesman::graph::solve_hamiltonian(&graph, time::Duration::seconds(1))
+//!    .is_err());
+//!}
+//!```
+//!
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+use time::Duration;
+
+use super::edges::Edges;
+use super::matrix::{DistanceMatrix, Weight};
+use super::oracle::DistanceOracle;
+use super::{validate_city, Error, Objective, Options, Shape, Tour};
+
+/// A graph stored as lists of the edges leaving each city
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Graph<W = f64> {
+    edges: Vec<Vec<(usize, W)>>,
+}
+
+impl<W: Weight> Graph<W> {
+    /// Returns a graph of `size` cities, without any edges
+    pub fn new(size: usize) -> Graph<W> {
+        Graph {
+            edges: vec![vec![]; size],
+        }
+    }
+
+    /// Returns a graph of `size` cities, joined by `(from, to, weight)` edges which can be
+    /// travelled in either direction
+    ///
+    /// Returns `travelling_salesman::Error::InvalidCity` if an edge joins a city outside the graph.
+    pub fn from_edges(size: usize, edges: &[(usize, usize, W)]) -> Result<Graph<W>, Error> {
+        let mut graph = Graph::new(size);
+
+        for &(from, to, weight) in edges {
+            graph.add_edge(from, to, weight)?;
+        }
+
+        Ok(graph)
+    }
+
+    /// Returns the number of cities
+    pub fn size(&self) -> usize {
+        self.edges.len()
+    }
+
+    /// Adds an edge which can be travelled in either direction
+    ///
+    /// Returns `travelling_salesman::Error::InvalidCity`, leaving the graph unchanged, if either
+    /// end is outside the graph.
+    pub fn add_edge(&mut self, from: usize, to: usize, weight: W) -> Result<(), Error> {
+        self.add_arc(from, to, weight)?;
+        self.add_arc(to, from, weight)
+    }
+
+    /// Adds an edge which can only be travelled from city `from` to city `to`, such as a one-way
+    /// street
+    ///
+    /// Returns `travelling_salesman::Error::InvalidCity`, leaving the graph unchanged, if either
+    /// end is outside the graph.
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::graph::Graph;
+    ///use travelling_salesman::Error;
+    ///
+    ///fn main() {
+    ///  let mut graph = Graph::new(2);
+    ///
+    ///  assert!(graph.add_arc(0, 1, 1.0).is_ok());
+    ///  assert_eq!(graph.add_arc(2, 0, 1.0), Err(Error::InvalidCity(2)));
+    ///  assert_eq!(graph.add_arc(0, 2, 1.0), Err(Error::InvalidCity(2)));
+    ///  assert_eq!(graph.edges(0), &[(1, 1.0)]);
+    ///}
+    ///```
+    pub fn add_arc(&mut self, from: usize, to: usize, weight: W) -> Result<(), Error> {
+        validate_city(from, self.size())?;
+        validate_city(to, self.size())?;
+
+        self.edges[from].push((to, weight));
+
+        Ok(())
+    }
+
+    /// Returns the `(to, weight)` edges leaving city `from`
+    pub fn edges(&self, from: usize) -> &[(usize, W)] {
+        &self.edges[from]
+    }
+
+    /// Returns the weight of the lightest edge from city `from` to city `to`, if there is one
+    pub fn edge(&self, from: usize, to: usize) -> Option<W> {
+        self.edges[from]
+            .iter()
+            .filter(|&&(city, _)| city == to)
+            .map(|&(_, weight)| weight)
+            .fold(None, |lightest, weight| match lightest {
+                Some(lightest) if lightest <= weight => Some(lightest),
+                _ => Some(weight),
+            })
+    }
+
+    /// Returns the shortest paths between every pair of cities, found using Dijkstra's algorithm
+    /// from each city
+    ///
+    /// Returns `travelling_salesman::Error::Infeasible` if some city cannot be reached from
+    /// another.
+    pub fn shortest_paths(&self) -> Result<ShortestPaths<W>, Error> {
+        self.validate()?;
+
+        let size = self.size();
+        let mut distances = DistanceMatrix::from_fn(size, |_, _| W::zero());
+        let mut previous = Vec::with_capacity(size);
+
+        for from in 0..size {
+            let (shortest, before) = self.dijkstra(from);
+
+            for (to, distance) in shortest.into_iter().enumerate() {
+                match distance {
+                    Some(distance) => distances.set(from, to, distance),
+                    None => {
+                        return Err(Error::Infeasible(format!(
+                            "city {} cannot be reached from city {}",
+                            to, from
+                        )))
+                    }
+                }
+            }
+
+            previous.push(before);
+        }
+
+        Ok(ShortestPaths {
+            distances,
+            previous,
+        })
+    }
+
+    /// Checks the edges, which may not have been added by `add_arc` if the graph was deserialised
+    fn validate(&self) -> Result<(), Error> {
+        for edges in &self.edges {
+            for &(to, _) in edges {
+                validate_city(to, self.size())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the shortest distance from `from` to each city, and the city before it on the
+    /// shortest path
+    fn dijkstra(&self, from: usize) -> (Vec<Option<W>>, Vec<usize>) {
+        let size = self.size();
+        let mut shortest: Vec<Option<W>> = vec![None; size];
+        let mut before: Vec<usize> = (0..size).collect();
+        let mut settled = vec![false; size];
+        let mut queue = BinaryHeap::new();
+
+        shortest[from] = Some(W::zero());
+        queue.push(Queued {
+            distance: 0.0,
+            city: from,
+        });
+
+        while let Some(Queued { city, .. }) = queue.pop() {
+            if settled[city] {
+                continue;
+            }
+
+            settled[city] = true;
+
+            let distance = match shortest[city] {
+                Some(distance) => distance,
+                None => continue,
+            };
+
+            for &(next, weight) in &self.edges[city] {
+                let next_distance = distance + weight;
+
+                if shortest[next].map_or(true, |current| next_distance < current) {
+                    shortest[next] = Some(next_distance);
+                    before[next] = city;
+                    queue.push(Queued {
+                        distance: next_distance.to_f64(),
+                        city: next,
+                    });
+                }
+            }
+        }
+
+        (shortest, before)
+    }
+}
+
+/// A city waiting to be settled by Dijkstra's algorithm, ordered so the nearest is popped first
+struct Queued {
+    distance: f64,
+    city: usize,
+}
+
+impl PartialEq for Queued {
+    fn eq(&self, other: &Queued) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Queued {}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Queued) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Queued) -> Ordering {
+        other
+            .distance
+            .partial_cmp(&self.distance)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.city.cmp(&self.city))
+    }
+}
+
+/// The shortest paths between every pair of cities of a graph
+///
+/// This is a complete distance matrix, which can be passed to any solver taking a
+/// `travelling_salesman::oracle::DistanceOracle`.
+pub struct ShortestPaths<W = f64> {
+    distances: DistanceMatrix<W>,
+    previous: Vec<Vec<usize>>,
+}
+
+impl<W: Weight> ShortestPaths<W> {
+    /// Returns the cities along the shortest path from city `from` to city `to`, including both
+    pub fn path(&self, from: usize, to: usize) -> Vec<usize> {
+        let mut path = vec![to];
+        let mut city = to;
+
+        while city != from {
+            city = self.previous[from][city];
+            path.push(city);
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// Returns `route` with the shortest path between each pair of consecutive cities filled in,
+    /// so that it only travels along the edges of the graph
+    pub fn expand(&self, route: &[usize]) -> Vec<usize> {
+        let mut expanded: Vec<usize> = route.iter().take(1).cloned().collect();
+
+        for pair in route.windows(2) {
+            expanded.extend(&self.path(pair[0], pair[1])[1..]);
+        }
+
+        expanded
+    }
+
+    /// Returns `tour` with its route expanded to travel only along the edges of the graph, which
+    /// may pass through some cities more than once
+    pub fn expand_tour(&self, tour: &Tour<W>) -> Tour<W> {
+        Tour {
+            distance: tour.distance,
+            route: self.expand(&tour.route),
+            closed: tour.closed,
+        }
+    }
+}
+
+impl<W: Weight> DistanceOracle for ShortestPaths<W> {
+    type Weight = W;
+
+    fn size(&self) -> usize {
+        self.distances.size()
+    }
+
+    fn distance(&self, from: usize, to: usize) -> W {
+        self.distances[(from, to)]
+    }
+}
+
+/// The state of the search for the shortest Hamiltonian cycle
+struct Hamiltonian<'a, W> {
+    graph: &'a Graph<W>,
+    edges: Edges,
+    visited: Vec<bool>,
+    route: Vec<usize>,
+    shortest: Option<(W, Vec<usize>)>,
+    start_time: Instant,
+    runtime: Duration,
+    timed_out: bool,
+}
+
+impl<'a, W: Weight> Hamiltonian<'a, W> {
+    /// Returns whether every unvisited city can still be entered and left
+    fn reachable(&self) -> bool {
+        let size = self.graph.size();
+        let start = self.route[0];
+        let current = self.route[self.route.len() - 1];
+
+        let mut entered = vec![false; size];
+        let mut left = vec![false; size];
+
+        for from in (0..size).filter(|&city| !self.visited[city] || city == current) {
+            for &(to, _) in self.graph.edges(from) {
+                if !self.visited[to] || to == start {
+                    entered[to] = true;
+                    left[from] = true;
+                }
+            }
+        }
+
+        (0..size).all(|city| self.visited[city] || (entered[city] && left[city]))
+    }
+
+    /// Returns whether the route may travel on to `next`, never along a forbidden edge, and
+    /// leaving along a fixed edge whenever the current city did not arrive along one
+    fn allowed(&self, next: usize) -> bool {
+        let current = self.route[self.route.len() - 1];
+
+        if self.edges.is_forbidden(current, next) {
+            return false;
+        }
+
+        let previous = if self.route.len() > 1 {
+            Some(self.route[self.route.len() - 2])
+        } else {
+            None
+        };
+
+        // the first city can still return along one of its fixed edges at the end
+
+        let owed = self
+            .edges
+            .fixed(current)
+            .iter()
+            .filter(|&&city| Some(city) != previous)
+            .count();
+
+        owed == 0 || self.edges.is_fixed(current, next) || (previous.is_none() && owed == 1)
+    }
+
+    fn search(&mut self, distance: W) {
+        if self.runtime <= self.start_time.elapsed() {
+            self.timed_out = true;
+            return;
+        }
+
+        if let Some((shortest, _)) = self.shortest {
+            if distance >= shortest {
+                return;
+            }
+        }
+
+        let current = self.route[self.route.len() - 1];
+        let start = self.route[0];
+
+        if self.route.len() == self.graph.size() {
+            if let Some(weight) = self.graph.edge(current, start) {
+                let distance = distance + weight;
+
+                if self
+                    .shortest
+                    .as_ref()
+                    .map_or(true, |&(shortest, _)| distance < shortest)
+                {
+                    let mut route = self.route.clone();
+                    route.push(start);
+
+                    if self.edges.violations(&route) == 0 {
+                        self.shortest = Some((distance, route));
+                    }
+                }
+            }
+
+            return;
+        }
+
+        if !self.reachable() {
+            return;
+        }
+
+        // try the lightest edges first, so a short cycle bounds the rest of the search early
+
+        let mut edges: Vec<(usize, W)> = self
+            .graph
+            .edges(current)
+            .iter()
+            .filter(|&&(city, _)| !self.visited[city] && self.allowed(city))
+            .cloned()
+            .collect();
+
+        edges.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+
+        for (city, weight) in edges {
+            if self.visited[city] {
+                continue;
+            }
+
+            self.visited[city] = true;
+            self.route.push(city);
+
+            self.search(distance + weight);
+
+            self.route.pop();
+            self.visited[city] = false;
+
+            if self.timed_out {
+                return;
+            }
+        }
+    }
+}
+
+/// Returns the shortest tour visiting every city of the graph exactly once, travelling only along
+/// its edges
+///
+/// The search is exact, but takes exponential time in the worst case, so stops after `runtime`
+/// with the shortest tour found so far.
+///
+///# Parameters and Return Type
+///
+/// `graph` is a `travelling_salesman::graph::Graph`.
+///
+/// `runtime` is a `time::Duration`, specifying the longest time to spend searching.
+///
+/// Returns a `travelling_salesman::Tour` struct, starting and finishing at city 0. Returns
+/// `travelling_salesman::Error::Infeasible` if no tour visits every city exactly once, or none was
+/// found within the runtime.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::graph::Graph;
+///
+///fn main() {
+///  let graph = Graph::from_edges(
+///    4,
+///    &[
+///      (0, 1, 1.0),
+///      (1, 2, 1.0),
+///      (2, 3, 1.0),
+///      (3, 0, 1.0),
+///      (0, 2, 5.0),
+///    ],
+///  )
+///  .unwrap();
+///
+///  let tour =
+///    travelling_salesman::graph::solve_hamiltonian(&graph, time::Duration::seconds(1)).unwrap();
+///
+///  assert_eq!(tour.distance, 4.0);
+///  assert_eq!(tour.route, vec![0, 1, 2, 3, 0]);
+///}
+///```
+pub fn solve_hamiltonian<W: Weight>(graph: &Graph<W>, runtime: Duration) -> Result<Tour<W>, Error> {
+    solve_hamiltonian_with_options(graph, runtime, &Options::default())
+}
+
+/// Returns the shortest tour visiting every city of the graph exactly once, travelling only along
+/// its edges, subject to the specified options
+///
+/// The search is exact, but takes exponential time in the worst case, so stops after `runtime`
+/// with the shortest tour found so far.
+///
+///# Parameters and Return Type
+///
+/// `graph` is a `travelling_salesman::graph::Graph`.
+///
+/// `runtime` is a `time::Duration`, specifying the longest time to spend searching.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which the start city and the fixed and
+/// forbidden edges are supported. The search makes no random choices, so the seed and the number
+/// of iterations have no effect. Any other shape or objective returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::Tour` struct, starting and finishing at the start city, or city 0
+/// if none is given. Returns `travelling_salesman::Error::Infeasible` if no tour visits every city
+/// exactly once along every fixed edge and no forbidden edge, or none was found within the
+/// runtime.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::graph::Graph;
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let graph = Graph::from_edges(
+///    4,
+///    &[
+///      (0, 1, 1.0),
+///      (1, 2, 1.0),
+///      (2, 3, 1.0),
+///      (3, 0, 1.0),
+///      (0, 2, 5.0),
+///      (1, 3, 5.0),
+///    ],
+///  )
+///  .unwrap();
+///
+///  let tour = travelling_salesman::graph::solve_hamiltonian_with_options(
+///    &graph,
+///    time::Duration::seconds(1),
+///    &Options {
+///      start_city: Some(1),
+///      fixed_edges: vec![(0, 2)],
+///      forbidden_edges: vec![(2, 3)],
+///      ..Options::default()
+///    },
+///  )
+///  .unwrap();
+///
+///  assert_eq!(tour.distance, 12.0);
+///  assert_eq!(tour.route, vec![1, 2, 0, 3, 1]);
+///}
+///```
+pub fn solve_hamiltonian_with_options<W: Weight>(
+    graph: &Graph<W>,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Tour<W>, Error> {
+    graph.validate()?;
+
+    let size = graph.size();
+
+    options.validate(size)?;
+
+    if options.shape != Shape::Cycle || options.objective != Objective::default() {
+        return Err(Error::InvalidOptions(
+            "Hamiltonian cycles only support the shortest closed tour".to_string(),
+        ));
+    }
+
+    let edges = Edges::new(
+        size,
+        Shape::Cycle,
+        &options.fixed_edges,
+        &options.forbidden_edges,
+    )?;
+
+    if size < 2 {
+        return Ok(Tour {
+            distance: W::zero(),
+            route: vec![0; size * 2],
+            closed: true,
+        });
+    }
+
+    let mut visited = vec![false; size];
+    visited[0] = true;
+
+    let mut search = Hamiltonian {
+        graph,
+        edges,
+        visited,
+        route: vec![0],
+        shortest: None,
+        start_time: Instant::now(),
+        runtime,
+        timed_out: false,
+    };
+
+    search.search(W::zero());
+
+    match search.shortest {
+        Some((distance, route)) => {
+            let mut tour = Tour {
+                distance,
+                route,
+                closed: true,
+            };
+
+            if let Some(start_city) = options.start_city {
+                tour.rotate_to(start_city);
+            }
+
+            Ok(tour)
+        }
+        None if search.timed_out => Err(Error::Infeasible(
+            "no Hamiltonian cycle was found within the runtime".to_string(),
+        )),
+        None if !search.edges.is_empty() => Err(Error::Infeasible(
+            "the graph has no Hamiltonian cycle along every fixed edge and no forbidden edge"
+                .to_string(),
+        )),
+        None => Err(Error::Infeasible(
+            "the graph has no Hamiltonian cycle".to_string(),
+        )),
+    }
+}
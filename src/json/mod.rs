@@ -0,0 +1,358 @@
+//! JSON formats for problems and solutions, enabled by the `serde` feature
+//!
+//! A problem gives either the coordinates of each city with a metric, or an explicit distance
+//! matrix, along with the solver to run and its options:
+//!
+//!```json
+//!{
+//!  "cities": [[27.0, 78.0], [18.0, 24.0], [48.0, 62.0], [83.0, 77.0], [55.0, 56.0]],
+//!  "metric": "euclidean",
+//!  "solver": "simulated_annealing",
+//!  "runtime_ms": 1000,
+//!  "options": {
+//!    "shape": "Cycle",
+//!    "start_city": 0,
+//!    "objective": "MinimiseDistance",
+//!    "fixed_edges": [[0, 2]],
+//!    "forbidden_edges": []
+//!  }
+//!}
+//!```
+//!
+//! * `cities` is a list of `[x, y]` coordinates, or `[latitude, longitude]` for the geographic
+//!   metrics.
+//! * `distances` is a square matrix of distances, used instead of `cities` and `metric`.
+//! * `metric` is one of `euclidean` (the default), `rounded_euclidean`, `ceiling_euclidean`,
+//!   `manhattan`, `chebyshev`, `haversine` (in kilometres), `vincenty` (in kilometres) or `geo`
+//!   (the TSPLIB `GEO` type), or a Minkowski distance of order `p`, written
+//!   `{"minkowski": {"p": 3.0}}`.
+//! * `solver` is one of `brute_force`, `hill_climbing`, `random_restarts`, `random_search` or
+//!   `simulated_annealing` (the default).
+//! * `restart_probability` is the probability of restarting for `random_restarts`, which defaults
+//!   to 0.01.
+//! * `runtime_ms` is how long to search for, in milliseconds, which defaults to 1000.
+//! * `options` are the `travelling_salesman::Options`, where every field may be left out. Shapes
+//!   are written `"Cycle"`, `"Path"`, `{"PathFrom": 0}` or `{"PathBetween": [0, 4]}`.
+//!
+//! A solution gives the route found and its distance, with a lower bound on the optimal distance
+//! if the solver proved one, and statistics about the search:
+//!
+//!```json
+//!{
+//!  "route": [0, 2, 3, 4, 1, 0],
+//!  "distance": 224.0,
+//!  "closed": true,
+//!  "bound": null,
+//!  "stats": {
+//!    "solver": "simulated_annealing",
+//!    "cities": 5,
+//!    "runtime_ms": 1000
+//!  }
+//!}
+//!```
+//!
+//!# Examples
+//!
+//!```
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::json::Problem;
+//!
+//!fn main() {
+//!  let problem = Problem::from_json(
+//!    r#"{
+//!      "distances": [[0, 3, 4], [3, 0, 5], [4, 5, 0]],
+//!      "solver": "brute_force"
+//!    }"#,
+//!  )
+//!  .unwrap();
+//!
+//!  let solution = problem.solve().unwrap();
+//!
+//!  assert_eq!(solution.distance, 12.0);
+//!  assert_eq!(solution.bound, Some(12.0));
+//!
+//!  println!("{}", solution.to_json());
+//!}
+//!```
+//!
+use serde_json;
+use std::time::Instant;
+use time::Duration;
+
+use super::metrics::{
+    CeilingEuclidean, Chebyshev, Euclidean, Geo, Haversine, Manhattan, Minkowski, RoundedEuclidean,
+    Vincenty,
+};
+use super::{
+    brute_force, get_distance_matrix_with_metric, hill_climbing, random_search,
+    simulated_annealing, Error, Objective, Options, Tour,
+};
+
+/// How distances are calculated from city coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricName {
+    /// `travelling_salesman::metrics::Euclidean`
+    #[default]
+    Euclidean,
+    /// `travelling_salesman::metrics::RoundedEuclidean`
+    RoundedEuclidean,
+    /// `travelling_salesman::metrics::CeilingEuclidean`
+    CeilingEuclidean,
+    /// `travelling_salesman::metrics::Manhattan`
+    Manhattan,
+    /// `travelling_salesman::metrics::Chebyshev`
+    Chebyshev,
+    /// `travelling_salesman::metrics::Minkowski`, of order `p`
+    Minkowski {
+        /// the order of the distance, which must be finite and at least `1.0`
+        p: f64,
+    },
+    /// `travelling_salesman::metrics::Haversine`, measuring in kilometres
+    Haversine,
+    /// `travelling_salesman::metrics::Vincenty`, measuring in kilometres
+    Vincenty,
+    /// `travelling_salesman::metrics::Geo`
+    Geo,
+}
+
+/// The solver used to search for a route
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Solver {
+    /// `travelling_salesman::brute_force`
+    BruteForce,
+    /// `travelling_salesman::hill_climbing`
+    HillClimbing,
+    /// `travelling_salesman::hill_climbing::random_restarts`
+    RandomRestarts,
+    /// `travelling_salesman::random_search`
+    RandomSearch,
+    /// `travelling_salesman::simulated_annealing`
+    #[default]
+    SimulatedAnnealing,
+}
+
+fn default_runtime_ms() -> u64 {
+    1000
+}
+
+fn default_restart_probability() -> f64 {
+    0.01
+}
+
+/// A problem, read from the JSON format described in the module documentation
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Problem {
+    /// the coordinates of each city, unless distances are given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cities: Vec<(f64, f64)>,
+    /// the distance matrix, unless cities are given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub distances: Vec<Vec<f64>>,
+    /// how distances between the cities are calculated
+    #[serde(default)]
+    pub metric: MetricName,
+    /// the solver to run
+    #[serde(default)]
+    pub solver: Solver,
+    /// how long to search for, in milliseconds
+    #[serde(default = "default_runtime_ms")]
+    pub runtime_ms: u64,
+    /// the probability of restarting, for `random_restarts`
+    #[serde(default = "default_restart_probability")]
+    pub restart_probability: f64,
+    /// the options passed to the solver
+    #[serde(default)]
+    pub options: Options,
+}
+
+/// Statistics about the search for a solution
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// the solver which was run
+    pub solver: Solver,
+    /// the number of cities in the problem
+    pub cities: usize,
+    /// how long the search took, in milliseconds
+    pub runtime_ms: u64,
+}
+
+/// A solution, written in the JSON format described in the module documentation
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Solution {
+    /// the ordered route found
+    pub route: Vec<usize>,
+    /// the total distance travelled following the route
+    pub distance: f64,
+    /// whether the route returns to the city it started from
+    pub closed: bool,
+    /// a lower bound on the distance of the best route, if the solver proved one
+    pub bound: Option<f64>,
+    /// statistics about the search
+    pub stats: Stats,
+}
+
+impl Problem {
+    /// Returns the problem read from `text`, or `travelling_salesman::Error::Parse` if it is not
+    /// valid
+    pub fn from_json(text: &str) -> Result<Problem, Error> {
+        serde_json::from_str(text).map_err(|error| Error::Parse(error.to_string()))
+    }
+
+    /// Returns the problem written as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("problems can always be written as JSON")
+    }
+
+    /// Returns the distance matrix of the problem
+    ///
+    /// Returns `travelling_salesman::Error::InvalidOptions` unless exactly one of the cities and
+    /// the distances are given, if the distance matrix is not square, or if the order of a
+    /// Minkowski distance is infinite or below `1.0`.
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::json::Problem;
+    ///
+    ///fn main() {
+    ///  let problem = Problem::from_json(
+    ///    r#"{
+    ///      "cities": [[0.0, 0.0], [3.0, 4.0]],
+    ///      "metric": {"minkowski": {"p": 1.0}}
+    ///    }"#,
+    ///  )
+    ///  .unwrap();
+    ///
+    ///  assert_eq!(problem.distance_matrix().unwrap()[0][1], 7.0);
+    ///}
+    ///```
+    pub fn distance_matrix(&self) -> Result<Vec<Vec<f64>>, Error> {
+        match (self.cities.is_empty(), self.distances.is_empty()) {
+            (false, true) => {
+                let cities = &self.cities;
+
+                Ok(match self.metric {
+                    MetricName::Euclidean => {
+                        get_distance_matrix_with_metric(cities, &Euclidean::new())
+                    }
+                    MetricName::RoundedEuclidean => {
+                        get_distance_matrix_with_metric(cities, &RoundedEuclidean::new())
+                    }
+                    MetricName::CeilingEuclidean => {
+                        get_distance_matrix_with_metric(cities, &CeilingEuclidean::new())
+                    }
+                    MetricName::Manhattan => {
+                        get_distance_matrix_with_metric(cities, &Manhattan::new())
+                    }
+                    MetricName::Chebyshev => {
+                        get_distance_matrix_with_metric(cities, &Chebyshev::new())
+                    }
+                    MetricName::Minkowski { p } => {
+                        get_distance_matrix_with_metric(cities, &Minkowski::new(p)?)
+                    }
+                    MetricName::Haversine => {
+                        get_distance_matrix_with_metric(cities, &Haversine::kilometres())
+                    }
+                    MetricName::Vincenty => {
+                        get_distance_matrix_with_metric(cities, &Vincenty::kilometres())
+                    }
+                    MetricName::Geo => get_distance_matrix_with_metric(cities, &Geo),
+                })
+            }
+            (true, false) => {
+                let size = self.distances.len();
+
+                if let Some(from) = self.distances.iter().position(|row| row.len() != size) {
+                    return Err(Error::InvalidOptions(format!(
+                        "expected {} distances from city {}, but got {}",
+                        size,
+                        from,
+                        self.distances[from].len()
+                    )));
+                }
+
+                Ok(self.distances.clone())
+            }
+            _ => Err(Error::InvalidOptions(
+                "the problem must give either cities or distances".to_string(),
+            )),
+        }
+    }
+
+    /// Runs the solver on the problem, returning its solution
+    ///
+    /// Returns any error from building the distance matrix or from the solver.
+    pub fn solve(&self) -> Result<Solution, Error> {
+        let distances = self.distance_matrix()?;
+        let runtime = Duration::milliseconds(self.runtime_ms as i64);
+        let start_time = Instant::now();
+
+        let tour: Tour = match self.solver {
+            Solver::BruteForce => brute_force::solve_with_options(&distances, &self.options)?,
+            Solver::HillClimbing => {
+                hill_climbing::solve_with_options(&distances, runtime, &self.options)?
+            }
+            Solver::RandomRestarts => hill_climbing::random_restarts::solve_with_options(
+                &distances,
+                runtime,
+                self.restart_probability,
+                &self.options,
+            )?,
+            Solver::RandomSearch => {
+                random_search::solve_with_options(&distances, runtime, &self.options)?
+            }
+            Solver::SimulatedAnnealing => {
+                simulated_annealing::solve_with_options(&distances, runtime, &self.options)?
+            }
+        };
+
+        // brute force proves its route is the shortest
+
+        let bound = if self.solver == Solver::BruteForce
+            && self.options.objective == Objective::MinimiseDistance
+        {
+            Some(tour.distance)
+        } else {
+            None
+        };
+
+        Ok(Solution {
+            route: tour.route,
+            distance: tour.distance,
+            closed: tour.closed,
+            bound,
+            stats: Stats {
+                solver: self.solver,
+                cities: distances.len(),
+                runtime_ms: start_time.elapsed().as_millis() as u64,
+            },
+        })
+    }
+}
+
+impl Solution {
+    /// Returns the solution read from `text`, or `travelling_salesman::Error::Parse` if it is not
+    /// valid
+    pub fn from_json(text: &str) -> Result<Solution, Error> {
+        serde_json::from_str(text).map_err(|error| Error::Parse(error.to_string()))
+    }
+
+    /// Returns the solution written as JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("solutions can always be written as JSON")
+    }
+
+    /// Returns the solution as a `travelling_salesman::Tour`
+    pub fn tour(&self) -> Tour {
+        Tour {
+            distance: self.distance,
+            route: self.route.clone(),
+            closed: self.closed,
+        }
+    }
+}
@@ -64,121 +64,1262 @@
 //! You should have received a copy of the GNU General Public License along with this program. If
 //! not, see [http://www.gnu.org/licenses/](http://www.gnu.org/licenses/).
 pub mod brute_force;
+pub mod clustered;
+pub mod dynamic;
+mod edges;
+pub mod generalized;
+pub mod generators;
+pub mod geographic;
+pub mod graph;
 pub mod hill_climbing;
+#[cfg(feature = "serde")]
+pub mod json;
+pub mod matrix;
+pub mod metrics;
+mod moves;
+pub mod mtsp;
+pub mod oracle;
+pub mod orienteering;
+pub mod pareto;
+pub mod precedence;
+pub mod prize_collecting;
 pub mod random_search;
+pub mod render;
+mod routes;
+mod search;
+mod selection;
 pub mod simulated_annealing;
+pub mod time_dependent;
+pub mod time_windows;
+pub mod tsplib;
+pub mod vrp;
 
-extern crate metaheuristics;
 extern crate rand;
 extern crate time;
 
-use metaheuristics::Metaheuristics;
-use rand::rngs::ThreadRng;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "png")]
+extern crate tiny_skia;
+
+use edges::Edges;
+use matrix::Weight;
+use metrics::{Euclidean, Metric};
+use oracle::DistanceOracle;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use search::{Budget, Metaheuristics};
+use std::collections::HashSet;
+use std::error;
+use std::fmt;
+use time::Duration;
 
-struct TravellingSalesman<'a> {
-    distance_matrix: &'a Vec<Vec<f64>>,
-    rng: &'a mut ThreadRng,
+struct TravellingSalesman<'a, D: DistanceOracle + ?Sized> {
+    distances: &'a D,
+    rng: &'a mut StdRng,
+    shape: Shape,
+    objective: Objective,
+    edges: &'a Edges,
+    symmetric: bool,
 }
 
+#[derive(Clone)]
 struct Candidate {
     route: Vec<usize>,
+    /// the position of each city in the route
+    positions: Vec<usize>,
+    /// the total distance of the route
+    distance: f64,
+    /// the number of fixed edges missing from the route, plus the forbidden edges it travels
+    violations: usize,
 }
 
-impl<'a> Metaheuristics<Candidate> for TravellingSalesman<'a> {
-    fn clone_candidate(&mut self, candidate: &Candidate) -> Candidate {
-        Candidate {
-            route: candidate.route.clone(),
+/// A change to a route, which can be evaluated from the few edges it changes
+#[derive(Clone, Copy)]
+enum Move {
+    /// swap the cities at two positions
+    Swap(usize, usize),
+    /// reverse the cities from the first position to the second
+    Reverse(usize, usize),
+}
+
+impl Move {
+    /// Returns the position of the city which the move brings to `position`
+    fn source(&self, position: usize) -> usize {
+        match *self {
+            Move::Swap(start, end) if position == start => end,
+            Move::Swap(start, end) if position == end => start,
+            Move::Reverse(start, end) if start <= position && position <= end => {
+                start + end - position
+            }
+            _ => position,
         }
     }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Metaheuristics<Candidate> for TravellingSalesman<'a, D> {
+    fn clone_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        candidate.clone()
+    }
 
     fn generate_candidate(&mut self) -> Candidate {
-        let mut route: Vec<usize> = self
-            .distance_matrix
-            .iter()
-            .enumerate()
-            .map(|(i, _)| i)
-            .collect();
-        route.shuffle(&mut self.rng);
+        // cities joined by fixed edges are kept together, in either direction
 
-        let home_city = route[0];
-        route.push(home_city);
+        let mut chains = self.edges.chains();
+        chains.shuffle(&mut self.rng);
 
-        Candidate { route }
+        for chain in &mut chains {
+            if self.rng.gen::<bool>() {
+                chain.reverse();
+            }
+        }
+
+        match self.shape {
+            Shape::Cycle | Shape::Path => {}
+            Shape::PathFrom(start) => move_chain(&mut chains, start, true),
+            Shape::PathBetween(start, end) => {
+                move_chain(&mut chains, start, true);
+                move_chain(&mut chains, end, false);
+            }
+        }
+
+        let mut route = self.join_chains(chains);
+        self.close_route(&mut route);
+
+        self.candidate(route)
     }
 
     fn rank_candidate(&mut self, candidate: &Candidate) -> f64 {
-        0.0 - get_route_distance(self.distance_matrix, &candidate.route)
+        match self.objective {
+            Objective::MinimiseDistance => 0.0 - candidate.distance,
+            Objective::MinimiseLongestEdge => self.objective.rank(self.distances, &candidate.route),
+            Objective::MaximiseDistance => candidate.distance,
+        }
     }
 
     fn tweak_candidate(&mut self, candidate: &Candidate) -> Candidate {
-        if candidate.route.len() <= 3 {
+        // only the cities between any fixed endpoints can be moved
+
+        let cities = self.cities(&candidate.route);
+        let (first, last) = self.shape.free_positions(cities);
+
+        if last < first + 2 {
             return self.clone_candidate(candidate);
         }
 
-        let mut old_route = candidate.route.clone();
-        old_route.pop();
+        // get two cities to work with, preferring a city and one of its nearest neighbours so that
+        // the reversal below joins them with a short edge, unless long edges are wanted
 
-        // get two cities to work with
+        let neighbouring_positions = match self.objective {
+            Objective::MaximiseDistance => None,
+            _ => self.get_neighbouring_positions(candidate),
+        };
 
-        let start = self.rng.gen::<usize>() % old_route.len();
-        let end = self.rng.gen::<usize>() % old_route.len();
+        let (start, end) = match neighbouring_positions {
+            Some((start, end)) if start >= first && end < last => (start, end),
+            _ => (
+                first + self.rng.gen::<usize>() % (last - first),
+                first + self.rng.gen::<usize>() % (last - first),
+            ),
+        };
         let (start, end) = if start < end {
             (start, end)
         } else {
             (end, start)
         };
 
-        // straight swap of the cities
+        // either swap the cities, or reverse the cities between them, keeping the better route
+        // unless it loses a fixed edge or adds a forbidden one
 
-        let mut swapped_route = old_route.clone();
-        swapped_route.swap(start, end);
+        let steps = [Move::Swap(start, end), Move::Reverse(start, end)];
+        let moves = steps
+            .iter()
+            .map(|&step| {
+                let (distance, violations) = self.evaluate(candidate, step);
+                (step, distance, violations)
+            })
+            .filter(|&(_, _, violations)| violations <= 0);
 
-        // swap cities, then reverse the cities between them
+        let best_candidate = if self.objective == Objective::MinimiseLongestEdge {
+            // the longest edge of the route cannot be found from the edges which change
 
-        let split_route = old_route.clone();
-        let safe_offset = if old_route.len() <= (end + 1) {
-            old_route.len()
+            moves
+                .map(|(step, distance, violations)| {
+                    self.make_move(candidate, step, distance, violations)
+                })
+                .max_by(|a, b| {
+                    self.objective
+                        .rank(self.distances, &a.route)
+                        .partial_cmp(&self.objective.rank(self.distances, &b.route))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
         } else {
-            end + 1
+            let gain = |distance: f64| match self.objective {
+                Objective::MaximiseDistance => distance,
+                _ => 0.0 - distance,
+            };
+
+            moves
+                .max_by(|a, b| {
+                    gain(a.1)
+                        .partial_cmp(&gain(b.1))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .map(|(step, distance, violations)| {
+                    self.make_move(candidate, step, distance, violations)
+                })
         };
-        let (left, right) = split_route.split_at(safe_offset);
-        let (left, middle) = left.split_at(start);
 
-        let mut middle = middle.to_vec();
-        middle.reverse();
+        match best_candidate {
+            Some(best_candidate) => best_candidate,
+            None => self.clone_candidate(candidate),
+        }
+    }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> TravellingSalesman<'a, D> {
+    /// Returns a candidate for `route`, measuring its distance and violations
+    fn candidate(&self, route: Vec<usize>) -> Candidate {
+        let mut positions = vec![0; self.distances.size()];
+
+        // the first city of a cycle is at the start, not at its return
+
+        for (position, &city) in route.iter().enumerate().rev() {
+            positions[city] = position;
+        }
+
+        Candidate {
+            distance: get_route_distance(self.distances, &route).to_f64(),
+            violations: self.edges.violations(&route),
+            route,
+            positions,
+        }
+    }
+
+    /// Returns the number of cities in `route`, excluding the return to the start of a cycle
+    fn cities(&self, route: &[usize]) -> usize {
+        match self.shape {
+            Shape::Cycle => route.len().saturating_sub(1),
+            _ => route.len(),
+        }
+    }
 
-        let mut reordered_route = Vec::new();
-        reordered_route.extend(left.iter());
-        reordered_route.extend(middle.iter());
-        reordered_route.extend(right.iter());
+    fn get_neighbouring_positions(&mut self, candidate: &Candidate) -> Option<(usize, usize)> {
+        let cities = self.cities(&candidate.route);
+        let position = self.rng.gen::<usize>() % cities;
+        let neighbours = self.distances.neighbours(candidate.route[position])?;
+        let neighbour = neighbours[self.rng.gen::<usize>() % neighbours.len()];
+        let neighbour_position = candidate.positions[neighbour];
 
-        // return shortest route
+        // reversing the cities after `position` up to the neighbour makes them adjacent
 
-        let swapped_distance = get_route_distance(self.distance_matrix, &swapped_route);
-        let reordered_distance = get_route_distance(self.distance_matrix, &reordered_route);
-        let mut shortest_route = if swapped_distance < reordered_distance {
-            swapped_route
+        if position < neighbour_position {
+            Some((position + 1, neighbour_position))
         } else {
-            reordered_route
+            Some((neighbour_position + 1, position))
+        }
+    }
+
+    /// Returns the edges of `route` which `step` changes, where edge `k` leaves position `k`
+    ///
+    /// Reversing cities keeps the edges between them, only changing their direction, so those
+    /// edges are only included when distances are not symmetric.
+    fn changed_edges(&self, route: &[usize], step: Move) -> Vec<usize> {
+        let edges = route.len() - 1;
+
+        let before = |position: usize| match position {
+            0 if self.shape == Shape::Cycle => Some(edges - 1),
+            0 => None,
+            _ => Some(position - 1),
         };
+        let after = |position: usize| Some(position).filter(|&position| position < edges);
 
-        let home_city = shortest_route[0];
-        shortest_route.push(home_city);
+        let mut changed: Vec<usize> = match step {
+            Move::Swap(start, end) => [before(start), after(start), before(end), after(end)]
+                .iter()
+                .flatten()
+                .cloned()
+                .collect(),
+            Move::Reverse(start, end) if self.symmetric => {
+                before(start).into_iter().chain(after(end)).collect()
+            }
+            Move::Reverse(start, end) => before(start)
+                .into_iter()
+                .chain(start..end)
+                .chain(after(end))
+                .collect(),
+        };
+
+        changed.sort_unstable();
+        changed.dedup();
+        changed
+    }
+
+    /// Returns the change in the distance of `candidate`, and in its violations of the fixed and
+    /// forbidden edges, if `step` were made
+    fn evaluate(&self, candidate: &Candidate, step: Move) -> (f64, isize) {
+        let route = &candidate.route;
+        let cities = self.cities(route);
+
+        // the return to the start of a cycle follows the city at the start
+
+        let moved = |position: usize| route[step.source(position % cities)];
+        let violations = |from: usize, to: usize| {
+            self.edges.is_forbidden(from, to) as isize - self.edges.is_fixed(from, to) as isize
+        };
+
+        let mut distance = 0.0;
+        let mut violation = 0;
+
+        for edge in self.changed_edges(route, step) {
+            let (from, to) = (route[edge], route[edge + 1]);
+            let (new_from, new_to) = (moved(edge), moved(edge + 1));
+
+            distance += self.distances.distance(new_from, new_to).to_f64()
+                - self.distances.distance(from, to).to_f64();
+
+            if !self.edges.is_empty() {
+                violation += violations(new_from, new_to) - violations(from, to);
+            }
+        }
+
+        (distance, violation)
+    }
+
+    /// Returns `candidate` with `step` made, which changes its distance and violations by those
+    /// given
+    fn make_move(
+        &self,
+        candidate: &Candidate,
+        step: Move,
+        distance: f64,
+        violations: isize,
+    ) -> Candidate {
+        let mut route = candidate.route.clone();
+        let mut positions = candidate.positions.clone();
+
+        let start = match step {
+            Move::Swap(start, end) => {
+                route.swap(start, end);
+                positions[route[start]] = start;
+                positions[route[end]] = end;
+                start
+            }
+            Move::Reverse(start, end) => {
+                route[start..=end].reverse();
+
+                for (position, &city) in route.iter().enumerate().take(end + 1).skip(start) {
+                    positions[city] = position;
+                }
+
+                start
+            }
+        };
+
+        // the return to the start of a cycle follows the city at the start
+
+        if self.shape == Shape::Cycle && start == 0 {
+            let last = route.len() - 1;
+            route[last] = route[0];
+        }
 
         Candidate {
-            route: shortest_route,
+            route,
+            positions,
+            distance: candidate.distance + distance,
+            violations: (candidate.violations as isize + violations) as usize,
+        }
+    }
+
+    /// Joins `chains` into a route, keeping the first and last chains in place, and preferring
+    /// the next chain in order which can follow without a forbidden edge
+    fn join_chains(&self, mut chains: Vec<Vec<usize>>) -> Vec<usize> {
+        if self.edges.is_empty() {
+            return chains.concat();
+        }
+
+        let last_chain = match self.shape {
+            Shape::PathBetween(_, _) if chains.len() > 1 => chains.pop(),
+            _ => None,
+        };
+
+        let mut route = vec![];
+
+        if !chains.is_empty() {
+            route = chains.remove(0);
+        }
+
+        while !chains.is_empty() {
+            let last = route[route.len() - 1];
+
+            let next = chains.iter().enumerate().find_map(|(index, chain)| {
+                if !self.edges.is_forbidden(last, chain[0]) {
+                    Some((index, false))
+                } else if !self.edges.is_forbidden(last, chain[chain.len() - 1]) {
+                    Some((index, true))
+                } else {
+                    None
+                }
+            });
+
+            let (index, reverse) = next.unwrap_or((0, false));
+            let mut chain = chains.remove(index);
+
+            if reverse {
+                chain.reverse();
+            }
+
+            route.extend(chain);
+        }
+
+        route.extend(last_chain.unwrap_or_default());
+        route
+    }
+
+    fn close_route(&self, route: &mut Vec<usize>) {
+        if self.shape == Shape::Cycle {
+            if let Some(&home_city) = route.first() {
+                route.push(home_city);
+            }
         }
     }
 }
 
+/// Searches for a tour of `distances` keeping `options`, using `search` to run a metaheuristic
+/// over the problem within the budget, and return the best candidate found
+fn solve_with<D, S>(
+    distances: &D,
+    runtime: Duration,
+    options: &Options,
+    search: S,
+) -> Result<Tour<D::Weight>, Error>
+where
+    D: DistanceOracle + ?Sized,
+    S: FnOnce(&mut TravellingSalesman<D>, &mut Budget, &mut StdRng) -> Candidate,
+{
+    options.validate(distances.size())?;
+
+    let edges = options.edges(distances.size())?;
+
+    // the search makes its own random choices apart from those of the problem, seeded from the
+    // same generator
+
+    let mut rng = options.rng();
+    let mut search_rng = StdRng::seed_from_u64(rng.gen());
+    let mut budget = options.budget(runtime);
+
+    let mut tsp = TravellingSalesman {
+        distances,
+        rng: &mut rng,
+        shape: options.shape(),
+        objective: options.objective,
+        edges: &edges,
+        symmetric: distances.symmetric(),
+    };
+
+    let best_candidate = search(&mut tsp, &mut budget, &mut search_rng);
+
+    edges.check(&best_candidate.route)?;
+
+    let mut tour = Tour {
+        distance: get_route_distance(distances, &best_candidate.route),
+        route: best_candidate.route,
+        closed: options.shape == Shape::Cycle,
+    };
+
+    if let Some(start_city) = options.start_city {
+        tour.rotate_to(start_city);
+    }
+
+    Ok(tour)
+}
+
+/// Moves the chain containing `city` to the start of `chains`, beginning with `city`, or to the
+/// end, finishing with `city`
+fn move_chain(chains: &mut Vec<Vec<usize>>, city: usize, first: bool) {
+    if let Some(index) = chains.iter().position(|chain| chain.contains(&city)) {
+        let mut chain = chains.remove(index);
+
+        if (chain[0] == city) != first {
+            chain.reverse();
+        }
+
+        if first {
+            chains.insert(0, chain);
+        } else {
+            chains.push(chain);
+        }
+    }
+}
+
+/// The shape of route to search for
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Shape {
+    /// a closed tour, returning to the city it started from
+    #[default]
+    Cycle,
+    /// an open path, starting and finishing at any city
+    Path,
+    /// an open path, starting at the specified city and finishing at any city
+    PathFrom(usize),
+    /// an open path, starting at the first specified city and finishing at the second
+    PathBetween(usize, usize),
+}
+
+impl Shape {
+    /// Returns the range of positions within a route of `size` cities (excluding the return to
+    /// the start of a cycle) which are not fixed endpoints
+    fn free_positions(&self, size: usize) -> (usize, usize) {
+        match *self {
+            Shape::Cycle | Shape::Path => (0, size),
+            Shape::PathFrom(_) => (1, size),
+            Shape::PathBetween(_, _) => (1, size.saturating_sub(1)),
+        }
+    }
+}
+
+/// What makes one route better than another
+///
+/// The distance of the tour returned is always its total distance, whatever the objective.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::{Objective, Options};
+///
+///fn main() {
+///  let distance_matrix = vec![
+///    vec![0.0, 1.0, 1.0, 9.0],
+///    vec![1.0, 0.0, 5.0, 5.0],
+///    vec![1.0, 5.0, 0.0, 5.0],
+///    vec![9.0, 5.0, 5.0, 0.0],
+///  ];
+///
+///  let bottleneck = travelling_salesman::brute_force::solve_with_options(
+///    &distance_matrix,
+///    &Options {
+///      objective: Objective::MinimiseLongestEdge,
+///      ..Options::default()
+///    },
+///  )
+///  .unwrap();
+///
+///  assert_eq!(
+///    travelling_salesman::get_longest_edge(&distance_matrix, &bottleneck.route),
+///    5.0
+///  );
+///
+///  let longest = travelling_salesman::brute_force::solve_with_options(
+///    &distance_matrix,
+///    &Options {
+///      objective: Objective::MaximiseDistance,
+///      ..Options::default()
+///    },
+///  )
+///  .unwrap();
+///
+///  assert_eq!(longest.distance, 20.0);
+///}
+///```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Objective {
+    /// the shortest total distance
+    #[default]
+    MinimiseDistance,
+    /// the shortest longest edge, then the shortest total distance, as in the Bottleneck
+    /// Travelling Salesman Problem
+    MinimiseLongestEdge,
+    /// the longest total distance, as in the Maximum Travelling Salesman Problem
+    MaximiseDistance,
+}
+
+impl Objective {
+    /// Returns a score for `route`, where higher is better
+    fn rank<D: DistanceOracle + ?Sized>(&self, distances: &D, route: &[usize]) -> f64 {
+        let distance = get_route_distance(distances, route).to_f64();
+
+        match *self {
+            Objective::MinimiseDistance => 0.0 - distance,
+            Objective::MinimiseLongestEdge => {
+                // the total distance breaks ties, while adding less than a millionth of the
+                // longest edge, since no edge is longer
+
+                let edges = route.len().max(2) - 1;
+                0.0 - get_longest_edge(distances, route).to_f64() - distance * 1e-6 / edges as f64
+            }
+            Objective::MaximiseDistance => distance,
+        }
+    }
+
+    /// Returns whether `route` is better than `best_route`, comparing weights exactly
+    fn better<D: DistanceOracle + ?Sized>(
+        &self,
+        distances: &D,
+        route: &[usize],
+        best_route: &[usize],
+    ) -> bool {
+        let distance = get_route_distance(distances, route);
+        let best_distance = get_route_distance(distances, best_route);
+
+        match *self {
+            Objective::MinimiseDistance => distance < best_distance,
+            Objective::MinimiseLongestEdge => {
+                let longest_edge = get_longest_edge(distances, route);
+                let best_longest_edge = get_longest_edge(distances, best_route);
+
+                longest_edge < best_longest_edge
+                    || (longest_edge == best_longest_edge && distance < best_distance)
+            }
+            Objective::MaximiseDistance => distance > best_distance,
+        }
+    }
+}
+
+/// Options controlling the route searched for by a solver
+///
+/// Every option is honoured by the Travelling Salesman solvers: `brute_force`, `hill_climbing`,
+/// `hill_climbing::random_restarts`, `random_search` and `simulated_annealing`. The search for
+/// Hamiltonian cycles, `graph::solve_hamiltonian_with_options`, honours the start city and the
+/// fixed and forbidden edges.
+///
+/// The solvers for other problems, such as `vrp`, `mtsp`, `time_windows`, `dynamic` and `pareto`,
+/// shape their routes by constraints of their own, and only support the seed and the number of
+/// iterations. They do not honour fixed or forbidden edges yet, so instead of silently ignoring
+/// them, they return `Error::InvalidOptions` if any other option is set.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::{Options, Shape};
+///
+///fn main() {
+///  let options = Options {
+///    shape: Shape::PathFrom(0),
+///    ..Options::default()
+///  };
+///
+///  assert_eq!(options.shape, Shape::PathFrom(0));
+///}
+///```
+///
+/// Always crossing the bridge between cities 0 and 3, and never taking the closed road between
+/// cities 1 and 2:
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::{Error, Options};
+///
+///fn main() {
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&[
+///    (27.0, 78.0),
+///    (18.0, 24.0),
+///    (48.0, 62.0),
+///    (83.0, 77.0),
+///    (55.0, 56.0),
+///  ]);
+///
+///  let tour = travelling_salesman::brute_force::solve_with_options(
+///    &distance_matrix,
+///    &Options {
+///      fixed_edges: vec![(0, 3)],
+///      forbidden_edges: vec![(1, 2)],
+///      ..Options::default()
+///    },
+///  )
+///  .unwrap();
+///
+///  assert!(tour.route.windows(2).any(|edge| edge == [0, 3] || edge == [3, 0]));
+///  assert!(!tour.route.windows(2).any(|edge| edge == [1, 2] || edge == [2, 1]));
+///
+///  // city 0 cannot have three fixed edges
+///
+///  let result = travelling_salesman::brute_force::solve_with_options(
+///    &distance_matrix,
+///    &Options {
+///      fixed_edges: vec![(0, 1), (0, 2), (0, 3)],
+///      ..Options::default()
+///    },
+///  );
+///
+///  assert!(matches!(result, Err(Error::Infeasible(_))));
+///}
+///```
+///
+/// Solvers for other problems reject the options they cannot honour:
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::{Error, Options};
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(10, 100.0, 7);
+///
+///  let result = travelling_salesman::clustered::solve_with_options(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    &[vec![0, 1, 2], vec![3, 4]],
+///    time::Duration::milliseconds(10),
+///    &Options {
+///      forbidden_edges: vec![(0, 1)],
+///      ..Options::default()
+///    },
+///  );
+///
+///  assert!(matches!(result, Err(Error::InvalidOptions(_))));
+///}
+///```
+///
+/// Finding the same route on every run:
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(50, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(10_000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::simulated_annealing::solve_with_options(
+///      &distance_matrix,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Options {
+    /// the shape of route to search for, which defaults to a closed tour
+    pub shape: Shape,
+    /// the city the route must start from, such as a depot
+    ///
+    /// Closed tours are rotated to begin and end at this city, and free paths start from it.
+    pub start_city: Option<usize>,
+    /// what makes one route better than another, which defaults to the shortest total distance
+    pub objective: Objective,
+    /// edges the route must travel along, as pairs of cities joined in either direction
+    ///
+    /// Only the Travelling Salesman solvers and the search for Hamiltonian cycles honour fixed and
+    /// forbidden edges.
+    pub fixed_edges: Vec<(usize, usize)>,
+    /// edges the route must never travel along, as pairs of cities joined in either direction
+    pub forbidden_edges: Vec<(usize, usize)>,
+    /// the seed for the random choices of the search, which are otherwise different on every run
+    ///
+    /// A search limited by its runtime makes as many moves as fit in it, so repeated runs can
+    /// still find different routes unless `iterations` is also given.
+    pub seed: Option<u64>,
+    /// the number of iterations to search for, instead of the runtime
+    ///
+    /// Together with `seed`, this makes every run find the same route.
+    pub iterations: Option<u64>,
+}
+
+impl Options {
+    /// Returns the shape to search for, with free paths starting at `start_city` if given
+    fn shape(&self) -> Shape {
+        match (self.shape, self.start_city) {
+            (Shape::Path, Some(start)) => Shape::PathFrom(start),
+            (shape, _) => shape,
+        }
+    }
+
+    /// Returns the fixed and forbidden edges, or `Error::Infeasible` if they cannot all be kept
+    fn edges(&self, size: usize) -> Result<Edges, Error> {
+        Edges::new(size, self.shape(), &self.fixed_edges, &self.forbidden_edges)
+    }
+
+    /// Returns a random number generator, seeded from `seed` if given
+    fn rng(&self) -> StdRng {
+        match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        }
+    }
+
+    /// Returns a budget of `iterations` if given, and of `runtime` otherwise
+    fn budget(&self, runtime: Duration) -> Budget {
+        Budget::new(runtime, self.iterations)
+    }
+
+    /// Returns `Error::InvalidOptions` if any option besides the seed and the number of iterations
+    /// is set, for solvers whose routes are shaped by their own constraints
+    fn validate_search_only(&self) -> Result<(), Error> {
+        let unsupported = [
+            (self.shape != Shape::default(), "a shape"),
+            (self.start_city.is_some(), "a start city"),
+            (self.objective != Objective::default(), "an objective"),
+            (!self.fixed_edges.is_empty(), "fixed edges"),
+            (!self.forbidden_edges.is_empty(), "forbidden edges"),
+        ];
+
+        match unsupported.iter().find(|&&(set, _)| set) {
+            Some(&(_, option)) => Err(Error::InvalidOptions(format!(
+                "this solver does not support {}, only a seed and a number of iterations",
+                option
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    fn validate(&self, size: usize) -> Result<(), Error> {
+        if let Some(start_city) = self.start_city {
+            validate_city(start_city, size)?;
+
+            match self.shape {
+                Shape::PathFrom(start) | Shape::PathBetween(start, _) if start != start_city => {
+                    return Err(Error::InvalidOptions(format!(
+                        "path starts at city {}, but the start city is {}",
+                        start, start_city
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        match self.shape {
+            Shape::Cycle | Shape::Path => Ok(()),
+            Shape::PathFrom(start) => validate_city(start, size),
+            Shape::PathBetween(start, end) => {
+                validate_city(start, size)?;
+                validate_city(end, size)?;
+
+                if start == end {
+                    return Err(Error::InvalidOptions(format!(
+                        "path must start and finish at different cities, but both are {}",
+                        start
+                    )));
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+fn validate_city(city: usize, size: usize) -> Result<(), Error> {
+    if city < size {
+        Ok(())
+    } else {
+        Err(Error::InvalidCity(city))
+    }
+}
+
+/// Checks there is at least one cluster, and every cluster holds cities of the problem which are
+/// not in any other cluster
+fn validate_clusters(clusters: &[Vec<usize>], size: usize) -> Result<(), Error> {
+    if clusters.is_empty() {
+        return Err(Error::InvalidOptions(
+            "there must be at least one cluster".to_string(),
+        ));
+    }
+
+    let mut clustered = vec![false; size];
+
+    for (index, cluster) in clusters.iter().enumerate() {
+        if cluster.is_empty() {
+            return Err(Error::InvalidOptions(format!("cluster {} is empty", index)));
+        }
+
+        for &city in cluster {
+            validate_city(city, size)?;
+
+            if clustered[city] {
+                return Err(Error::InvalidOptions(format!(
+                    "city {} is in more than one cluster",
+                    city
+                )));
+            }
+
+            clustered[city] = true;
+        }
+    }
+
+    Ok(())
+}
+
+/// Errors returned by solvers
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Error {
+    /// a city index is outside the problem
+    InvalidCity(usize),
+    /// the options are inconsistent, described by the message
+    InvalidOptions(String),
+    /// a tour does not visit every city exactly once, described by the message
+    InvalidTour(String),
+    /// no route satisfying the constraints was found, for the reason described by the message
+    Infeasible(String),
+    /// an input file is malformed, described by the message
+    Parse(String),
+    /// an input file could not be read, described by the message
+    Io(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::InvalidCity(city) => write!(f, "city {} is not in the problem", city),
+            Error::InvalidOptions(ref message) => write!(f, "invalid options: {}", message),
+            Error::InvalidTour(ref message) => write!(f, "invalid tour: {}", message),
+            Error::Infeasible(ref message) => write!(f, "infeasible: {}", message),
+            Error::Parse(ref message) => write!(f, "parse error: {}", message),
+            Error::Io(ref message) => write!(f, "I/O error: {}", message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
 /// Represents a tour of the travelling salesman
-pub struct Tour {
+///
+/// The distance is an `f64` unless the tour was solved using integer weights.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tour<W = f64> {
     /// the total distance travelled following this tour
-    pub distance: f64,
+    pub distance: W,
     /// the ordered route for this tour
     pub route: Vec<usize>,
+    /// whether the route returns to the city it started from
+    pub closed: bool,
+}
+
+impl<W> Tour<W> {
+    /// Rotates a closed tour so that it begins and ends at `city`
+    ///
+    /// Open paths, and tours not visiting `city`, are left unchanged.
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let mut tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![2, 0, 3, 1, 2],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  tour.rotate_to(3);
+    ///
+    ///  assert_eq!(tour.route, vec![3, 1, 2, 0, 3]);
+    ///}
+    ///```
+    pub fn rotate_to(&mut self, city: usize) {
+        if !self.closed || self.route.is_empty() {
+            return;
+        }
+
+        if let Some(position) = self.route[..self.route.len() - 1]
+            .iter()
+            .position(|&c| c == city)
+        {
+            self.route.pop();
+            self.route.rotate_left(position);
+            self.route.push(city);
+        }
+    }
+
+    /// Checks that the route visits each of `size` cities exactly once, returning to the first
+    /// city if the tour is closed
+    ///
+    /// Returns `travelling_salesman::Error::InvalidCity` if the route visits a city outside the
+    /// problem, or `travelling_salesman::Error::InvalidTour` describing how else it is wrong.
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::{Error, Tour};
+    ///
+    ///fn main() {
+    ///  let tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![0, 2, 1, 0],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  assert_eq!(tour.validate(3), Ok(()));
+    ///  assert_eq!(tour.validate(2), Err(Error::InvalidCity(2)));
+    ///  assert!(matches!(tour.validate(4), Err(Error::InvalidTour(_))));
+    ///}
+    ///```
+    pub fn validate(&self, size: usize) -> Result<(), Error> {
+        let stops = self.stops();
+
+        if self.closed && self.route.len() > 1 && self.route.first() != self.route.last() {
+            return Err(Error::InvalidTour(
+                "the closed route does not return to its first city".to_string(),
+            ));
+        }
+
+        let mut visited = vec![false; size];
+
+        for &city in stops {
+            validate_city(city, size)?;
+
+            if visited[city] {
+                return Err(Error::InvalidTour(format!(
+                    "city {} is visited more than once",
+                    city
+                )));
+            }
+
+            visited[city] = true;
+        }
+
+        match visited.iter().position(|&visited| !visited) {
+            Some(city) => Err(Error::InvalidTour(format!("city {} is not visited", city))),
+            None => Ok(()),
+        }
+    }
+
+    /// Rotates and reverses the route into a canonical form, so that equivalent tours have the
+    /// same route
+    ///
+    /// A closed tour starts from its smallest city, and visits the smaller of its neighbours
+    /// next. An open path starts from the smaller of its ends.
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let mut tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![2, 3, 0, 1, 2],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  tour.canonicalise();
+    ///
+    ///  assert_eq!(tour.route, vec![0, 1, 2, 3, 0]);
+    ///}
+    ///```
+    pub fn canonicalise(&mut self) {
+        self.route = self.canonical_route();
+    }
+
+    fn canonical_route(&self) -> Vec<usize> {
+        let mut stops = self.stops().to_vec();
+
+        if stops.len() < 2 {
+            return self.route.clone();
+        }
+
+        if self.closed {
+            if let Some(smallest) = (0..stops.len()).min_by_key(|&position| stops[position]) {
+                stops.rotate_left(smallest);
+            }
+
+            if stops[stops.len() - 1] < stops[1] {
+                stops[1..].reverse();
+            }
+
+            stops.push(stops[0]);
+        } else if stops[stops.len() - 1] < stops[0] {
+            stops.reverse();
+        }
+
+        stops
+    }
+
+    /// Returns the cities visited, without repeating the first city at the end of a closed tour
+    fn stops(&self) -> &[usize] {
+        if self.closed && self.route.len() > 1 {
+            &self.route[..self.route.len() - 1]
+        } else {
+            &self.route
+        }
+    }
+
+    /// Returns an iterator over the `(from, to)` edges travelled, in route order
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![0, 2, 1, 0],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  let edges: Vec<(usize, usize)> = tour.edges().collect();
+    ///
+    ///  assert_eq!(edges, vec![(0, 2), (2, 1), (1, 0)]);
+    ///}
+    ///```
+    pub fn edges<'a>(&'a self) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.route.windows(2).map(|pair| (pair[0], pair[1]))
+    }
+
+    /// Returns whether the tour travels the edge between `from` and `to`, in either direction
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![0, 2, 1, 3, 0],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  assert!(tour.contains_edge(1, 2));
+    ///  assert!(!tour.contains_edge(0, 1));
+    ///}
+    ///```
+    pub fn contains_edge(&self, from: usize, to: usize) -> bool {
+        self.edges()
+            .any(|edge| edge == (from, to) || edge == (to, from))
+    }
+
+    /// Returns the bond distance to `other`, which is the number of edges of this tour which
+    /// `other` does not travel in either direction
+    ///
+    /// Equivalent tours have a bond distance of zero.
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![0, 1, 2, 3, 0],
+    ///    closed: true,
+    ///  };
+    ///  let other = Tour {
+    ///    distance: 12.0,
+    ///    route: vec![0, 2, 1, 3, 0],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  assert_eq!(tour.bond_distance(&other), 2);
+    ///}
+    ///```
+    pub fn bond_distance<V>(&self, other: &Tour<V>) -> usize {
+        let other_edges: HashSet<(usize, usize)> = other
+            .edges()
+            .map(|(from, to)| (from.min(to), from.max(to)))
+            .collect();
+
+        self.edges()
+            .filter(|&(from, to)| !other_edges.contains(&(from.min(to), from.max(to))))
+            .count()
+    }
+
+    /// Returns whether `other` visits the cities in the same order, allowing for a different
+    /// first city of a closed tour, and for travelling in the opposite direction
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let tour = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![0, 1, 2, 3, 0],
+    ///    closed: true,
+    ///  };
+    ///  let other = Tour {
+    ///    distance: 10.0,
+    ///    route: vec![2, 1, 0, 3, 2],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  assert!(tour.is_equivalent(&other));
+    ///}
+    ///```
+    pub fn is_equivalent<V>(&self, other: &Tour<V>) -> bool {
+        self.closed == other.closed && self.canonical_route() == other.canonical_route()
+    }
+
+    /// Recalculates the distance of the route using `distance_matrix`, returning the new distance
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::Tour;
+    ///
+    ///fn main() {
+    ///  let mut tour = Tour {
+    ///    distance: 0.0,
+    ///    route: vec![0, 1, 2, 0],
+    ///    closed: true,
+    ///  };
+    ///
+    ///  tour.recompute(&travelling_salesman::get_distance_matrix(&[(0.0, 0.0), (3.0, 4.0), (3.0, 0.0)]));
+    ///
+    ///  assert_eq!(tour.distance, 12.0);
+    ///}
+    ///```
+    pub fn recompute<D: DistanceOracle<Weight = W> + ?Sized>(&mut self, distance_matrix: &D) -> W
+    where
+        W: Weight,
+    {
+        self.distance = get_route_distance(distance_matrix, &self.route);
+        self.distance
+    }
+}
+
+impl<W: fmt::Display> fmt::Display for Tour<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let route: Vec<String> = self.route.iter().map(|city| city.to_string()).collect();
+
+        write!(f, "{} (distance {})", route.join(" -> "), self.distance)
+    }
 }
 
 /// Utility function to convert city coordinates to a distance matrix
@@ -207,12 +1348,52 @@ pub struct Tour {
 ///}
 ///```
 pub fn get_distance_matrix(cities: &[(f64, f64)]) -> Vec<Vec<f64>> {
+    get_distance_matrix_with_metric(cities, &Euclidean::new())
+}
+
+/// Utility function to convert city coordinates to a distance matrix using the specified metric
+///
+/// `cities` is an array slice, containing the coordinates for each city.
+///
+/// `metric` is a `travelling_salesman::metrics::Metric`, specifying how to measure the distance
+/// between two cities.
+///
+/// Returns a `Vec<Vec<f64>>`, containing the distance matrix.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::metrics::{Euclidean, Vincenty};
+///
+///fn main() {
+///    let cities = [
+///      (-33.8688, 151.2093),
+///      (-37.8136, 144.9631),
+///      (-27.4698, 153.0251),
+///    ];
+///
+///    let distance_matrix =
+///      travelling_salesman::get_distance_matrix_with_metric(&cities, &Vincenty::kilometres());
+///
+///    println!("The distance between 0 and 1 is: {}km", distance_matrix[0][1]);
+///
+///    let distance_matrix = travelling_salesman::get_distance_matrix_with_metric(
+///      &[[0.0, 0.0, 0.0], [1.0, 2.0, 2.0], [4.0, 6.0, 2.0]],
+///      &Euclidean::new(),
+///    );
+///
+///    println!("The distance between 1 and 2 is: {}", distance_matrix[1][2]);
+///}
+///```
+pub fn get_distance_matrix_with_metric<P, M: Metric<P>>(cities: &[P], metric: &M) -> Vec<Vec<f64>> {
     cities
         .iter()
         .map(|row| {
             cities
                 .iter()
-                .map(|column| ((column.0 - row.0).powi(2) + (column.1 - row.1).powi(2)).sqrt())
+                .map(|column| metric.distance(row, column))
                 .collect::<Vec<f64>>()
         })
         .collect::<Vec<Vec<f64>>>()
@@ -220,11 +1401,13 @@ pub fn get_distance_matrix(cities: &[(f64, f64)]) -> Vec<Vec<f64>> {
 
 /// Utility function to calculate the distance travelled following the specified route
 ///
-/// `distance_matrix` is a `&Vec<Vec<f64>>` containing the distance matrix.
+/// `distance_matrix` is a `travelling_salesman::oracle::DistanceOracle`, such as the
+/// `Vec<Vec<f64>>` distance matrix returned by `get_distance_matrix`.
 ///
 /// `route` is a `&Vec<usize>`, containing the route of the travelling salesman.
 ///
-/// Returns an `f64`, representing the distance of the route travelled.
+/// Returns the distance of the route travelled, which is an `f64` for the distance matrix returned by
+/// `get_distance_matrix`, or an exact integer sum for integer weights.
 ///
 ///# Examples
 ///
@@ -248,16 +1431,59 @@ pub fn get_distance_matrix(cities: &[(f64, f64)]) -> Vec<Vec<f64>> {
 ///    println!("The route distance for the tour [0, 2, 3, 4, 1, 0] is {}", route_distance);
 ///}
 ///```
-pub fn get_route_distance(distance_matrix: &[Vec<f64>], route: &[usize]) -> f64 {
+pub fn get_route_distance<D: DistanceOracle + ?Sized>(
+    distance_matrix: &D,
+    route: &[usize],
+) -> D::Weight {
     let mut route_iter = route.iter();
     let mut current_city = match route_iter.next() {
-        None => return 0.0,
+        None => return D::Weight::zero(),
         Some(v) => *v,
     };
 
-    route_iter.fold(0.0, |mut total_distance, &next_city| {
-        total_distance += distance_matrix[current_city as usize][next_city as usize];
+    route_iter.fold(D::Weight::zero(), |total_distance, &next_city| {
+        let total_distance = total_distance + distance_matrix.distance(current_city, next_city);
         current_city = next_city;
         total_distance
     })
 }
+
+/// Utility function to find the longest edge travelled following the specified route
+///
+/// `distance_matrix` is a `travelling_salesman::oracle::DistanceOracle`, such as the
+/// `Vec<Vec<f64>>` distance matrix returned by `get_distance_matrix`.
+///
+/// `route` is a `&Vec<usize>`, containing the route of the travelling salesman.
+///
+/// Returns the distance of the longest edge, which is minimised by
+/// `travelling_salesman::Objective::MinimiseLongestEdge`, or zero for a route without edges.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///fn main() {
+///    let longest_edge = travelling_salesman::get_longest_edge(
+///      &travelling_salesman::get_distance_matrix(&[(0.0, 0.0), (3.0, 4.0), (3.0, 0.0)]),
+///      &vec![0, 1, 2, 0]
+///    );
+///
+///    assert_eq!(longest_edge, 5.0);
+///}
+///```
+pub fn get_longest_edge<D: DistanceOracle + ?Sized>(
+    distance_matrix: &D,
+    route: &[usize],
+) -> D::Weight {
+    route
+        .windows(2)
+        .map(|edge| distance_matrix.distance(edge[0], edge[1]))
+        .fold(D::Weight::zero(), |longest, distance| {
+            if distance > longest {
+                distance
+            } else {
+                longest
+            }
+        })
+}
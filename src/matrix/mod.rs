@@ -0,0 +1,401 @@
+//! Compact distance matrix storage
+//!
+//! `DistanceMatrix` stores every distance in a single contiguous row-major buffer, avoiding the
+//! double indirection of a `Vec<Vec<f64>>`. `TriangularDistanceMatrix` stores only the upper
+//! triangle of a symmetric matrix, halving memory again. Both can hold `f64`, `f32`, `u32` or
+//! `i64` weights.
+//!
+//! Solving with integer weights, as TSPLIB defines them, makes every route distance an exact sum,
+//! so ties and optimality comparisons are exact too.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::matrix::TriangularDistanceMatrix;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (27.0, 78.0),
+//!    (18.0, 24.0),
+//!    (48.0, 62.0),
+//!    (83.0, 77.0),
+//!    (55.0, 56.0),
+//!  ];
+//!
+//!  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+//!  let distances = TriangularDistanceMatrix::<f32>::from(distance_matrix.as_slice());
+//!
+//!  let tour = travelling_salesman::simulated_annealing::solve_with_oracle(
+//!    &distances,
+//!    time::Duration::seconds(1),
+//!  );
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+//! Converting an owned matrix:
+//!
+//!```
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::matrix::{DistanceMatrix, TriangularDistanceMatrix};
+//!use travelling_salesman::oracle::DistanceOracle;
+//!
+//!fn main() {
+//!  let distance_matrix = vec![
+//!    vec![0.0, 3.0, 4.0],
+//!    vec![3.0, 0.0, 5.0],
+//!    vec![4.0, 5.0, 0.0],
+//!  ];
+//!
+//!  let triangular: TriangularDistanceMatrix<u32> = distance_matrix.clone().into();
+//!  let contiguous: DistanceMatrix<f32> = distance_matrix.into();
+//!
+//!  assert_eq!(triangular.distance(2, 1), 5);
+//!  assert_eq!(contiguous.distance(0, 2), 4.0);
+//!}
+//!```
+//!
+//! Solving exactly with TSPLIB `EUC_2D` integer distances:
+//!
+//!```
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::matrix::DistanceMatrix;
+//!use travelling_salesman::metrics::RoundedEuclidean;
+//!
+//!fn main() {
+//!  let distances = DistanceMatrix::<i64>::from_metric(
+//!    &[
+//!       (27.0, 78.0),
+//!       (18.0, 24.0),
+//!       (48.0, 62.0),
+//!       (83.0, 77.0),
+//!       (55.0, 56.0),
+//!    ],
+//!    &RoundedEuclidean::new(),
+//!  );
+//!
+//!  let tour = travelling_salesman::brute_force::solve_with_oracle(&distances);
+//!
+//!  assert_eq!(tour.distance, 203);
+//!}
+//!```
+//!
+use std::fmt::Debug;
+use std::ops::{Add, Index};
+
+use super::metrics::Metric;
+use super::oracle::DistanceOracle;
+
+/// A type which can be stored as a distance matrix entry, and summed to give a route distance
+///
+/// Integer weights are summed exactly, so comparisons between routes are free of floating point
+/// rounding. Note that `u32` sums can overflow on long routes, so prefer `i64` when solving with
+/// integer weights.
+pub trait Weight: Copy + Debug + PartialOrd + Add<Output = Self> {
+    /// Returns the weight of a route with no edges
+    fn zero() -> Self;
+
+    /// Converts a distance to this weight type, rounding to the nearest integer if needed
+    fn from_f64(distance: f64) -> Self;
+
+    /// Converts this weight to a distance
+    fn to_f64(self) -> f64;
+}
+
+impl Weight for f64 {
+    fn zero() -> f64 {
+        0.0
+    }
+
+    fn from_f64(distance: f64) -> f64 {
+        distance
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+}
+
+impl Weight for f32 {
+    fn zero() -> f32 {
+        0.0
+    }
+
+    fn from_f64(distance: f64) -> f32 {
+        distance as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Weight for u32 {
+    fn zero() -> u32 {
+        0
+    }
+
+    fn from_f64(distance: f64) -> u32 {
+        distance.round() as u32
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+impl Weight for i64 {
+    fn zero() -> i64 {
+        0
+    }
+
+    fn from_f64(distance: f64) -> i64 {
+        distance.round() as i64
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+}
+
+/// A square distance matrix stored contiguously in row-major order
+///
+/// Reading or setting the distance to or from a city outside the matrix panics.
+pub struct DistanceMatrix<W = f64> {
+    size: usize,
+    weights: Vec<W>,
+}
+
+impl<W: Weight> DistanceMatrix<W> {
+    /// Returns a `size` by `size` matrix, calling `distance(from, to)` for each entry
+    pub fn from_fn<F: FnMut(usize, usize) -> W>(size: usize, mut distance: F) -> DistanceMatrix<W> {
+        let mut weights = Vec::with_capacity(size * size);
+
+        for from in 0..size {
+            for to in 0..size {
+                weights.push(distance(from, to));
+            }
+        }
+
+        DistanceMatrix { size, weights }
+    }
+
+    /// Returns the matrix of distances between `cities` measured using `metric`
+    ///
+    ///# Examples
+    ///
+    ///```
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::matrix::DistanceMatrix;
+    ///use travelling_salesman::metrics::Euclidean;
+    ///
+    ///fn main() {
+    ///  let distances = DistanceMatrix::<u32>::from_metric(
+    ///    &[(0.0, 0.0), (3.0, 4.0), (6.0, 8.0)],
+    ///    &Euclidean::new(),
+    ///  );
+    ///
+    ///  assert_eq!(distances[(0, 2)], 10);
+    ///}
+    ///```
+    pub fn from_metric<P, M: Metric<P>>(cities: &[P], metric: &M) -> DistanceMatrix<W> {
+        DistanceMatrix::from_fn(cities.len(), |from, to| {
+            W::from_f64(metric.distance(&cities[from], &cities[to]))
+        })
+    }
+
+    /// Returns the number of cities
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the distances from city `from` to every city
+    pub fn row(&self, from: usize) -> &[W] {
+        self.check(from, 0);
+        &self.weights[from * self.size..(from + 1) * self.size]
+    }
+
+    /// Sets the distance from city `from` to city `to`
+    pub fn set(&mut self, from: usize, to: usize, weight: W) {
+        self.check(from, to);
+        self.weights[from * self.size + to] = weight;
+    }
+}
+
+impl<W> DistanceMatrix<W> {
+    fn check(&self, from: usize, to: usize) {
+        assert!(
+            from < self.size && to < self.size,
+            "cities ({}, {}) out of range for a matrix of {} cities",
+            from,
+            to,
+            self.size
+        );
+    }
+}
+
+impl<W> Index<(usize, usize)> for DistanceMatrix<W> {
+    type Output = W;
+
+    fn index(&self, (from, to): (usize, usize)) -> &W {
+        self.check(from, to);
+        &self.weights[from * self.size + to]
+    }
+}
+
+impl<'a, W: Weight> From<&'a [Vec<f64>]> for DistanceMatrix<W> {
+    fn from(distance_matrix: &'a [Vec<f64>]) -> DistanceMatrix<W> {
+        DistanceMatrix::from_fn(distance_matrix.len(), |from, to| {
+            W::from_f64(distance_matrix[from][to])
+        })
+    }
+}
+
+impl<W: Weight> From<Vec<Vec<f64>>> for DistanceMatrix<W> {
+    fn from(distance_matrix: Vec<Vec<f64>>) -> DistanceMatrix<W> {
+        DistanceMatrix::from(distance_matrix.as_slice())
+    }
+}
+
+impl<W: Weight> DistanceOracle for DistanceMatrix<W> {
+    type Weight = W;
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn distance(&self, from: usize, to: usize) -> W {
+        self[(from, to)]
+    }
+}
+
+/// A symmetric distance matrix storing only the entries above the diagonal
+///
+/// The distance from a city to itself is always zero. Reading or setting the distance to or from
+/// a city outside the matrix panics.
+pub struct TriangularDistanceMatrix<W = f64> {
+    size: usize,
+    weights: Vec<W>,
+}
+
+impl<W: Weight> TriangularDistanceMatrix<W> {
+    /// Returns a `size` by `size` matrix, calling `distance(from, to)` for each entry where
+    /// `from < to`
+    pub fn from_fn<F: FnMut(usize, usize) -> W>(
+        size: usize,
+        mut distance: F,
+    ) -> TriangularDistanceMatrix<W> {
+        let mut weights = Vec::with_capacity(size * size.saturating_sub(1) / 2);
+
+        for from in 0..size {
+            for to in (from + 1)..size {
+                weights.push(distance(from, to));
+            }
+        }
+
+        TriangularDistanceMatrix { size, weights }
+    }
+
+    /// Returns the matrix of distances between `cities` measured using the symmetric `metric`
+    pub fn from_metric<P, M: Metric<P>>(cities: &[P], metric: &M) -> TriangularDistanceMatrix<W> {
+        TriangularDistanceMatrix::from_fn(cities.len(), |from, to| {
+            W::from_f64(metric.distance(&cities[from], &cities[to]))
+        })
+    }
+
+    /// Returns the number of cities
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the distance between city `from` and city `to`
+    ///
+    ///# Examples
+    ///
+    ///```should_panic
+    ///extern crate travelling_salesman;
+    ///
+    ///use travelling_salesman::matrix::TriangularDistanceMatrix;
+    ///
+    ///fn main() {
+    ///  let distances = TriangularDistanceMatrix::from_fn(3, |from, to| (from + to) as f64);
+    ///
+    ///  assert_eq!(distances.get(2, 1), 3.0);
+    ///
+    ///  // city 5 is outside the matrix
+    ///
+    ///  distances.get(0, 5);
+    ///}
+    ///```
+    pub fn get(&self, from: usize, to: usize) -> W {
+        match self.offset(from, to) {
+            Some(offset) => self.weights[offset],
+            None => W::zero(),
+        }
+    }
+
+    /// Sets the distance between city `from` and city `to`, in both directions
+    ///
+    /// Setting the distance from a city to itself has no effect.
+    pub fn set(&mut self, from: usize, to: usize, weight: W) {
+        if let Some(offset) = self.offset(from, to) {
+            self.weights[offset] = weight;
+        }
+    }
+
+    fn offset(&self, from: usize, to: usize) -> Option<usize> {
+        assert!(
+            from < self.size && to < self.size,
+            "cities ({}, {}) out of range for a matrix of {} cities",
+            from,
+            to,
+            self.size
+        );
+
+        let (row, column) = match from.cmp(&to) {
+            std::cmp::Ordering::Less => (from, to),
+            std::cmp::Ordering::Greater => (to, from),
+            std::cmp::Ordering::Equal => return None,
+        };
+
+        Some(row * (2 * self.size - row - 1) / 2 + column - row - 1)
+    }
+}
+
+/// Takes the upper triangle of `distance_matrix`, which is assumed to be symmetric
+impl<'a, W: Weight> From<&'a [Vec<f64>]> for TriangularDistanceMatrix<W> {
+    fn from(distance_matrix: &'a [Vec<f64>]) -> TriangularDistanceMatrix<W> {
+        TriangularDistanceMatrix::from_fn(distance_matrix.len(), |from, to| {
+            W::from_f64(distance_matrix[from][to])
+        })
+    }
+}
+
+impl<W: Weight> From<Vec<Vec<f64>>> for TriangularDistanceMatrix<W> {
+    fn from(distance_matrix: Vec<Vec<f64>>) -> TriangularDistanceMatrix<W> {
+        TriangularDistanceMatrix::from(distance_matrix.as_slice())
+    }
+}
+
+impl<W: Weight> DistanceOracle for TriangularDistanceMatrix<W> {
+    type Weight = W;
+
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn distance(&self, from: usize, to: usize) -> W {
+        self.get(from, to)
+    }
+
+    fn symmetric(&self) -> bool {
+        true
+    }
+}
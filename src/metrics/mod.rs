@@ -0,0 +1,479 @@
+//! Distance metrics used to build distance matrices from city coordinates
+//!
+//! Cities can be any `Point`, such as `(x,y)` or `(x,y,z)` tuples, fixed size arrays or vectors.
+//! These are measured with `Euclidean`, `Manhattan`, `Chebyshev` or `Minkowski` distances, as well
+//! as the rounded variants used by TSPLIB. Geographic coordinates, given as `(latitude,
+//! longitude)` tuples in decimal degrees, are measured along the surface of the Earth using either
+//! `Haversine` (spherical) or `Vincenty` (WGS-84 ellipsoidal). `Geo` implements the TSPLIB `GEO`
+//! edge weight type for compatibility with published instances.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::metrics::Haversine;
+//!
+//!fn main() {
+//!  let tour = travelling_salesman::simulated_annealing::solve_with_metric(
+//!    &[
+//!       (-33.8688, 151.2093), // Sydney
+//!       (-37.8136, 144.9631), // Melbourne
+//!       (-27.4698, 153.0251), // Brisbane
+//!       (-34.9285, 138.6007), // Adelaide
+//!       (-35.2809, 149.1300), // Canberra
+//!    ],
+//!    &Haversine::kilometres(),
+//!    time::Duration::seconds(1),
+//!  );
+//!
+//!  println!("Tour distance: {}km, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+//! Picking locations in a warehouse, given as `(aisle, bay, shelf)` coordinates, for a picker
+//! that can only move along the aisles:
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::metrics::Manhattan;
+//!
+//!fn main() {
+//!  let tour = travelling_salesman::hill_climbing::solve_with_metric(
+//!    &[
+//!       (0.0, 0.0, 0.0),
+//!       (3.0, 12.0, 1.0),
+//!       (7.0, 4.0, 3.0),
+//!       (2.0, 9.0, 0.0),
+//!       (5.0, 1.0, 2.0),
+//!    ],
+//!    &Manhattan::new(),
+//!    time::Duration::seconds(1),
+//!  );
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+use super::Error;
+
+/// Mean radius of the Earth in kilometres
+pub const EARTH_RADIUS_KILOMETRES: f64 = 6371.0088;
+
+/// Semi-major axis of the WGS-84 ellipsoid in metres
+const WGS84_A: f64 = 6378137.0;
+
+/// Flattening of the WGS-84 ellipsoid
+const WGS84_F: f64 = 1.0 / 298.257223563;
+
+/// Radius of the Earth in kilometres as defined by TSPLIB
+const TSPLIB_RADIUS: f64 = 6378.388;
+
+/// TSPLIB's truncated value of pi, kept so that distances match published instances
+#[allow(clippy::approx_constant)]
+const TSPLIB_PI: f64 = 3.141592;
+
+/// A city location with one or more coordinates
+///
+/// The metrics measuring along each axis panic if two points have different dimensions.
+pub trait Point {
+    /// Returns the number of coordinates
+    fn dimensions(&self) -> usize;
+
+    /// Returns the coordinate along `axis`, counting from zero
+    fn coordinate(&self, axis: usize) -> f64;
+}
+
+impl Point for (f64, f64) {
+    fn dimensions(&self) -> usize {
+        2
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        match axis {
+            0 => self.0,
+            1 => self.1,
+            _ => panic!("axis {} out of range for a 2-D point", axis),
+        }
+    }
+}
+
+impl Point for (f64, f64, f64) {
+    fn dimensions(&self) -> usize {
+        3
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        match axis {
+            0 => self.0,
+            1 => self.1,
+            2 => self.2,
+            _ => panic!("axis {} out of range for a 3-D point", axis),
+        }
+    }
+}
+
+impl<const N: usize> Point for [f64; N] {
+    fn dimensions(&self) -> usize {
+        N
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        self[axis]
+    }
+}
+
+impl Point for Vec<f64> {
+    fn dimensions(&self) -> usize {
+        self.len()
+    }
+
+    fn coordinate(&self, axis: usize) -> f64 {
+        self[axis]
+    }
+}
+
+/// A way of measuring the distance between two cities located at points of type `P`
+///
+/// Metrics measure between `(x, y)` tuples unless another `Point` type is given, so the same
+/// metric, such as `Euclidean`, can measure between points with any number of coordinates.
+///
+/// The distance must be the same in both directions, which solvers measuring with a metric rely
+/// on.
+pub trait Metric<P = (f64, f64)> {
+    /// Returns the distance from `from` to `to`
+    fn distance(&self, from: &P, to: &P) -> f64;
+}
+
+/// Returns the absolute difference along each axis between two points
+fn differences<'a, P: Point>(from: &'a P, to: &'a P) -> impl Iterator<Item = f64> + 'a {
+    assert_eq!(
+        from.dimensions(),
+        to.dimensions(),
+        "cannot measure between points with different dimensions"
+    );
+
+    (0..from.dimensions()).map(move |axis| (to.coordinate(axis) - from.coordinate(axis)).abs())
+}
+
+macro_rules! point_metric {
+    ($(#[$attribute:meta])* $name:ident) => {
+        $(#[$attribute])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct $name;
+
+        impl $name {
+            #[doc = concat!("Returns a `", stringify!($name), "` metric, the same as `", stringify!($name), "`")]
+            pub fn new() -> $name {
+                $name
+            }
+        }
+    };
+}
+
+point_metric! {
+    /// Straight line distance between points in any number of dimensions
+    Euclidean
+}
+
+impl<P: Point> Metric<P> for Euclidean {
+    fn distance(&self, from: &P, to: &P) -> f64 {
+        differences(from, to).map(|d| d * d).sum::<f64>().sqrt()
+    }
+}
+
+point_metric! {
+    /// Euclidean distance rounded to the nearest integer, as in the TSPLIB `EUC_2D` and `EUC_3D`
+    /// edge weight types
+    RoundedEuclidean
+}
+
+impl<P: Point> Metric<P> for RoundedEuclidean {
+    fn distance(&self, from: &P, to: &P) -> f64 {
+        (Euclidean::new().distance(from, to) + 0.5).floor()
+    }
+}
+
+point_metric! {
+    /// Euclidean distance rounded up to the next integer, as in the TSPLIB `CEIL_2D` edge weight
+    /// type
+    CeilingEuclidean
+}
+
+impl<P: Point> Metric<P> for CeilingEuclidean {
+    fn distance(&self, from: &P, to: &P) -> f64 {
+        Euclidean::new().distance(from, to).ceil()
+    }
+}
+
+point_metric! {
+    /// Sum of the distances along each axis, as travelled by a robot moving along a grid
+    Manhattan
+}
+
+impl<P: Point> Metric<P> for Manhattan {
+    fn distance(&self, from: &P, to: &P) -> f64 {
+        differences(from, to).sum()
+    }
+}
+
+point_metric! {
+    /// Largest of the distances along each axis, as travelled by a machine moving along every
+    /// axis at once
+    Chebyshev
+}
+
+impl<P: Point> Metric<P> for Chebyshev {
+    fn distance(&self, from: &P, to: &P) -> f64 {
+        differences(from, to).fold(0.0, f64::max)
+    }
+}
+
+/// Minkowski distance of order `p`
+///
+/// An order of `1.0` is the `Manhattan` distance, and `2.0` is the `Euclidean` distance. Orders
+/// below `1.0` break the triangle inequality, so are not metrics. The limit of an infinite order
+/// is the `Chebyshev` distance, which should be used instead.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::metrics::{Metric, Minkowski};
+///
+///fn main() {
+///  let metric = Minkowski::new(3.0).unwrap();
+///
+///  assert!((metric.distance(&(0.0, 0.0), &(3.0, 4.0)) - 91f64.cbrt()).abs() < 1e-12);
+///  assert!(Minkowski::new(0.5).is_err());
+///  assert!(Minkowski::new(f64::INFINITY).is_err());
+///}
+///```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Minkowski {
+    p: f64,
+}
+
+impl Minkowski {
+    /// Returns a `Minkowski` metric of order `p`, or `travelling_salesman::Error::InvalidOptions`
+    /// unless `p` is finite and at least `1.0`
+    pub fn new(p: f64) -> Result<Minkowski, Error> {
+        if p == f64::INFINITY {
+            Err(Error::InvalidOptions(
+                "Minkowski order must be finite, so use Chebyshev for an infinite order"
+                    .to_string(),
+            ))
+        } else if p >= 1.0 {
+            Ok(Minkowski { p })
+        } else {
+            Err(Error::InvalidOptions(format!(
+                "Minkowski order must be at least 1, but is {}",
+                p
+            )))
+        }
+    }
+
+    /// Returns the order of the distance
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+}
+
+impl<P: Point> Metric<P> for Minkowski {
+    fn distance(&self, from: &P, to: &P) -> f64 {
+        differences(from, to)
+            .map(|d| d.powf(self.p))
+            .sum::<f64>()
+            .powf(1.0 / self.p)
+    }
+}
+
+/// Great-circle distance between `(latitude, longitude)` coordinates on a sphere
+///
+/// The result is in the same unit as `radius`.
+pub struct Haversine {
+    /// the radius of the sphere
+    pub radius: f64,
+}
+
+impl Haversine {
+    /// Returns a `Haversine` metric measuring in kilometres on the Earth
+    pub fn kilometres() -> Haversine {
+        Haversine {
+            radius: EARTH_RADIUS_KILOMETRES,
+        }
+    }
+
+    /// Returns a `Haversine` metric measuring in metres on the Earth
+    pub fn metres() -> Haversine {
+        Haversine {
+            radius: EARTH_RADIUS_KILOMETRES * 1000.0,
+        }
+    }
+}
+
+impl Metric for Haversine {
+    fn distance(&self, from: &(f64, f64), to: &(f64, f64)) -> f64 {
+        let (latitude1, longitude1) = (from.0.to_radians(), from.1.to_radians());
+        let (latitude2, longitude2) = (to.0.to_radians(), to.1.to_radians());
+
+        let h = ((latitude2 - latitude1) / 2.0).sin().powi(2)
+            + latitude1.cos() * latitude2.cos() * ((longitude2 - longitude1) / 2.0).sin().powi(2);
+
+        2.0 * self.radius * h.sqrt().min(1.0).asin()
+    }
+}
+
+/// Geodesic distance between `(latitude, longitude)` coordinates on the WGS-84 ellipsoid
+///
+/// Distances are calculated using Vincenty's inverse formula, which is accurate to within a
+/// millimetre. For nearly antipodal points where the formula fails to converge, the spherical
+/// `Haversine` distance is returned instead.
+pub struct Vincenty {
+    /// the number of metres in the returned unit
+    pub metres_per_unit: f64,
+}
+
+impl Vincenty {
+    /// Returns a `Vincenty` metric measuring in kilometres
+    pub fn kilometres() -> Vincenty {
+        Vincenty {
+            metres_per_unit: 1000.0,
+        }
+    }
+
+    /// Returns a `Vincenty` metric measuring in metres
+    pub fn metres() -> Vincenty {
+        Vincenty {
+            metres_per_unit: 1.0,
+        }
+    }
+}
+
+impl Metric for Vincenty {
+    fn distance(&self, from: &(f64, f64), to: &(f64, f64)) -> f64 {
+        let b = WGS84_A * (1.0 - WGS84_F);
+
+        let u1 = ((1.0 - WGS84_F) * from.0.to_radians().tan()).atan();
+        let u2 = ((1.0 - WGS84_F) * to.0.to_radians().tan()).atan();
+        let (sin_u1, cos_u1) = u1.sin_cos();
+        let (sin_u2, cos_u2) = u2.sin_cos();
+
+        let l = (to.1 - from.1).to_radians();
+        let mut lambda = l;
+
+        for _ in 0..200 {
+            let (sin_lambda, cos_lambda) = lambda.sin_cos();
+
+            let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+                + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+            .sqrt();
+
+            if sin_sigma == 0.0 {
+                return 0.0;
+            }
+
+            let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+            let sigma = sin_sigma.atan2(cos_sigma);
+            let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+            let cos_sq_alpha = 1.0 - sin_alpha.powi(2);
+
+            // both cities are on the equator
+
+            let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+                0.0
+            } else {
+                cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+            };
+
+            let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+            let previous_lambda = lambda;
+            lambda = l
+                + (1.0 - c)
+                    * WGS84_F
+                    * sin_alpha
+                    * (sigma
+                        + c * sin_sigma
+                            * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+
+            if (lambda - previous_lambda).abs() < 1e-12 {
+                let u_sq = cos_sq_alpha * (WGS84_A.powi(2) - b.powi(2)) / b.powi(2);
+                let big_a = 1.0
+                    + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+                let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+                let delta_sigma = big_b
+                    * sin_sigma
+                    * (cos_2sigma_m
+                        + big_b / 4.0
+                            * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                                - big_b / 6.0
+                                    * cos_2sigma_m
+                                    * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                    * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+
+                return b * big_a * (sigma - delta_sigma) / self.metres_per_unit;
+            }
+        }
+
+        Haversine {
+            radius: EARTH_RADIUS_KILOMETRES * 1000.0 / self.metres_per_unit,
+        }
+        .distance(from, to)
+    }
+}
+
+/// The TSPLIB `GEO` edge weight type
+///
+/// Coordinates are `(latitude, longitude)` tuples in TSPLIB's `DDD.MM` format, where the integer
+/// part is degrees and the fractional part is minutes. Distances are whole kilometres, exactly as
+/// computed by TSPLIB, so that tour lengths can be compared against published optima.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::metrics::{Geo, Metric};
+///
+///fn main() {
+///  // Burma 14, cities 1 and 2
+///
+///  assert_eq!(Geo.distance(&(16.47, 96.10), &(16.47, 94.44)), 153.0);
+///  assert_eq!(Geo.distance(&(16.47, 96.10), &(16.47, 96.1000001)), 1.0);
+///}
+///```
+pub struct Geo;
+
+impl Geo {
+    fn to_radians(coordinate: f64) -> f64 {
+        let degrees = coordinate.trunc();
+        let minutes = coordinate - degrees;
+
+        TSPLIB_PI * (degrees + 5.0 * minutes / 3.0) / 180.0
+    }
+}
+
+impl Metric for Geo {
+    fn distance(&self, from: &(f64, f64), to: &(f64, f64)) -> f64 {
+        if from == to {
+            return 0.0;
+        }
+
+        let (latitude1, longitude1) = (Geo::to_radians(from.0), Geo::to_radians(from.1));
+        let (latitude2, longitude2) = (Geo::to_radians(to.0), Geo::to_radians(to.1));
+
+        let q1 = (longitude1 - longitude2).cos();
+        let q2 = (latitude1 - latitude2).cos();
+        let q3 = (latitude1 + latitude2).cos();
+
+        // rounding can push the cosine of nearby cities just past 1.0, where acos is NaN
+
+        let cosine = (0.5 * ((1.0 + q1) * q2 - (1.0 - q1) * q3)).clamp(-1.0, 1.0);
+
+        (TSPLIB_RADIUS * cosine.acos() + 1.0).trunc()
+    }
+}
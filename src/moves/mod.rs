@@ -0,0 +1,44 @@
+//! Random moves between routes, shared by the local searches which evaluate routes incrementally
+//!
+//! Each move returns the first position at which the new route differs, so that the schedule up to
+//! that position can be reused.
+//!
+use rand::Rng;
+
+/// Returns a random neighbouring route, and the first position at which it differs
+pub fn random_move<R: Rng>(rng: &mut R, route: &[usize]) -> (usize, Vec<usize>) {
+    match rng.gen_range(0..3) {
+        0 => random_relocation(rng, route),
+        1 => {
+            let (i, j) = random_positions(rng, route.len());
+            let mut next_route = route.to_vec();
+            next_route.swap(i, j);
+            (i, next_route)
+        }
+        _ => {
+            let (i, j) = random_positions(rng, route.len());
+            let mut next_route = route.to_vec();
+            next_route[i..=j].reverse();
+            (i, next_route)
+        }
+    }
+}
+
+/// Moves a random city to a random position
+pub fn random_relocation<R: Rng>(rng: &mut R, route: &[usize]) -> (usize, Vec<usize>) {
+    let from = rng.gen_range(0..route.len());
+    let to = rng.gen_range(0..route.len());
+    let mut next_route = route.to_vec();
+    let city = next_route.remove(from);
+    next_route.insert(to, city);
+
+    (from.min(to), next_route)
+}
+
+/// Returns two distinct positions in ascending order
+fn random_positions<R: Rng>(rng: &mut R, len: usize) -> (usize, usize) {
+    let i = rng.gen_range(0..len);
+    let j = (i + rng.gen_range(1..len)) % len;
+
+    (i.min(j), i.max(j))
+}
@@ -0,0 +1,300 @@
+//! Find an approximate solution to the Multiple Travelling Salesman Problem
+//!
+//! The cities are split among several salesmen, each of whom starts and finishes at their own
+//! depot. Salesmen can share a depot by giving the same city more than once. Every city other
+//! than the depots is visited by exactly one salesman.
+//!
+//! Either the total distance travelled by every salesman, or the distance of the longest tour
+//! (the makespan) can be minimised. Minimising the longest tour balances the workload between
+//! salesmen.
+//!
+//! Tours are built by cheapest insertion, then improved by moving cities between tours
+//! (relocate, exchange and 2-opt*), and by solving each tour as a Travelling Salesman Problem
+//! using this crate's solvers.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::mtsp::Objective;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (50.0, 50.0),
+//!    (10.0, 10.0),
+//!    (20.0, 10.0),
+//!    (90.0, 90.0),
+//!    (80.0, 90.0),
+//!    (90.0, 80.0),
+//!  ];
+//!
+//!  let tours = travelling_salesman::mtsp::solve(
+//!    &travelling_salesman::get_distance_matrix(&cities),
+//!    &[0, 0],
+//!    Objective::LongestTour,
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  for tour in tours {
+//!    println!("Salesman distance: {}, route: {:?}", tour.distance, tour.route);
+//!  }
+//!}
+//!```
+//!
+use std::time::Instant;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::routes::{Balance, Fleet};
+use super::{get_route_distance, validate_city, Error, Options, Tour};
+
+/// What to minimise
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Objective {
+    /// the total distance travelled by every salesman
+    TotalDistance,
+    /// the distance travelled by the busiest salesman, then the total distance
+    LongestTour,
+}
+
+/// Returns an approximate solution to the Multiple Travelling Salesman Problem
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `depots` is an array slice, containing the depot of each salesman. Salesmen sharing a depot
+/// list the same city.
+///
+/// `objective` is a `travelling_salesman::mtsp::Objective`, specifying what to minimise.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `Vec` of `travelling_salesman::Tour` structs, one for each salesman in the order of
+/// `depots`, where idle salesmen stay at their depot. Returns
+/// `travelling_salesman::Error::InvalidOptions` if there are no salesmen.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::mtsp::Objective;
+///
+///fn main() {
+///  let cities = [
+///    (0.0, 0.0),
+///    (100.0, 0.0),
+///    (10.0, 0.0),
+///    (20.0, 0.0),
+///    (80.0, 0.0),
+///    (90.0, 0.0),
+///  ];
+///
+///  let tours = travelling_salesman::mtsp::solve(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    &[0, 1],
+///    Objective::TotalDistance,
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(tours[0].distance, 40.0);
+///  assert_eq!(tours[1].distance, 40.0);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    depots: &[usize],
+    objective: Objective,
+    runtime: Duration,
+) -> Result<Vec<Tour<D::Weight>>, Error> {
+    solve_with_options(distances, depots, objective, runtime, &Options::default())
+}
+
+/// Returns an approximate solution to the Multiple Travelling Salesman Problem, seeded and limited
+/// by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `depots` is an array slice, containing the depot of each salesman. Salesmen sharing a depot
+/// list the same city.
+///
+/// `objective` is a `travelling_salesman::mtsp::Objective`, specifying what to minimise.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations of each stage of the search instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `Vec` of `travelling_salesman::Tour` structs, one for each salesman in the order of
+/// `depots`, where idle salesmen stay at their depot. Returns
+/// `travelling_salesman::Error::InvalidOptions` if there are no salesmen.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::mtsp::Objective;
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(30, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::mtsp::solve_with_options(
+///      &distance_matrix,
+///      &[0, 1, 2],
+///      Objective::LongestTour,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    depots: &[usize],
+    objective: Objective,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Vec<Tour<D::Weight>>, Error> {
+    options.validate_search_only()?;
+
+    let size = distances.size();
+
+    if depots.is_empty() {
+        return Err(Error::InvalidOptions(
+            "there must be at least one salesman".to_string(),
+        ));
+    }
+
+    for &depot in depots {
+        validate_city(depot, size)?;
+    }
+
+    let balance = match objective {
+        Objective::TotalDistance => Balance::Sum,
+        Objective::LongestTour => Balance::Max,
+    };
+
+    let demands = vec![0; size];
+    let routes = insert_cities(distances, depots, balance);
+    let mut fleet = Fleet::new(
+        distances,
+        &demands,
+        u64::MAX,
+        balance,
+        depots.to_vec(),
+        routes,
+    );
+
+    let start_time = Instant::now();
+
+    fleet.improve(&mut options.budget(runtime / 2));
+
+    let remaining = runtime - start_time.elapsed();
+    fleet.optimise_routes(remaining / (depots.len() as u32), options);
+    fleet.improve(&mut options.budget(runtime - start_time.elapsed()));
+
+    Ok((0..depots.len())
+        .map(|index| {
+            let route = fleet.closed_route(index);
+
+            Tour {
+                distance: get_route_distance(distances, &route),
+                route,
+                closed: true,
+            }
+        })
+        .collect())
+}
+
+/// Inserts every city other than the depots at its cheapest position, starting with the cities
+/// furthest from any depot
+///
+/// When balancing, each city goes to the position which leaves its tour the shortest.
+fn insert_cities<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    depots: &[usize],
+    balance: Balance,
+) -> Vec<Vec<usize>> {
+    let distance = |from: usize, to: usize| distances.distance(from, to).to_f64();
+
+    let nearest_depot = |city: usize| {
+        depots
+            .iter()
+            .map(|&depot| distance(depot, city))
+            .fold(f64::INFINITY, f64::min)
+    };
+
+    let mut cities: Vec<(f64, usize)> = (0..distances.size())
+        .filter(|city| !depots.contains(city))
+        .map(|city| (nearest_depot(city), city))
+        .collect();
+
+    cities.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut routes: Vec<Vec<usize>> = vec![vec![]; depots.len()];
+    let mut costs = vec![0.0; depots.len()];
+
+    for (_, city) in cities {
+        let mut best: Option<(f64, f64, usize, usize)> = None;
+
+        for (index, route) in routes.iter().enumerate() {
+            let depot = depots[index];
+
+            for position in 0..=route.len() {
+                let previous = if position == 0 {
+                    depot
+                } else {
+                    route[position - 1]
+                };
+                let next = route.get(position).cloned().unwrap_or(depot);
+
+                let increase =
+                    distance(previous, city) + distance(city, next) - distance(previous, next);
+
+                let key = match balance {
+                    Balance::Sum => increase,
+                    Balance::Max => costs[index] + increase,
+                };
+
+                if best.map_or(true, |(best_key, best_increase, _, _)| {
+                    key < best_key || (key == best_key && increase < best_increase)
+                }) {
+                    best = Some((key, increase, index, position));
+                }
+            }
+        }
+
+        if let Some((_, increase, index, position)) = best {
+            routes[index].insert(position, city);
+            costs[index] += increase;
+        }
+    }
+
+    routes
+}
@@ -0,0 +1,227 @@
+//! Distance oracles, answering distance queries between cities by index
+//!
+//! A distance matrix built by `get_distance_matrix` needs `n²` entries, which is 80 GB for
+//! 100,000 cities. `LazyDistances` instead calculates each distance on demand from the city
+//! coordinates, so that memory grows linearly with the number of cities. It can optionally cache
+//! the nearest neighbours of each city, which the local search solvers use to focus their moves on
+//! short edges.
+//!
+//! The local search solvers evaluate each move from the few edges it changes, and keep the
+//! position of every city so a neighbour can be found without searching the route. Each move
+//! still copies the route and the positions, so takes time linear in the number of cities. This
+//! removes the memory limit on the number of cities, but not the time limit: the larger the
+//! problem, the fewer moves a search makes within its runtime.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::metrics::Euclidean;
+//!use travelling_salesman::oracle::LazyDistances;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (27.0, 78.0),
+//!    (18.0, 24.0),
+//!    (48.0, 62.0),
+//!    (83.0, 77.0),
+//!    (55.0, 56.0),
+//!  ];
+//!
+//!  let distances = LazyDistances::new(&cities, Euclidean::new()).with_neighbours(3);
+//!
+//!  let tour = travelling_salesman::hill_climbing::solve_with_oracle(
+//!    &distances,
+//!    time::Duration::seconds(1),
+//!  );
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+use super::matrix::Weight;
+use super::metrics::Metric;
+
+/// Answers distance queries between cities, identified by their index
+pub trait DistanceOracle {
+    /// The type of distance returned, such as `f64`, or `i64` for exact integer arithmetic
+    type Weight: Weight;
+
+    /// Returns the number of cities
+    fn size(&self) -> usize;
+
+    /// Returns the distance from city `from` to city `to`
+    fn distance(&self, from: usize, to: usize) -> Self::Weight;
+
+    /// Returns the cached nearest neighbours of `city`, closest first, if there are any
+    fn neighbours(&self, _city: usize) -> Option<&[usize]> {
+        None
+    }
+
+    /// Returns whether the distance between every pair of cities is the same in both directions
+    ///
+    /// The local search solvers then evaluate reversing part of a route from the two edges at its
+    /// ends, rather than every edge reversed. This should only return `true` when it is known
+    /// without comparing every pair of cities.
+    fn symmetric(&self) -> bool {
+        false
+    }
+}
+
+impl<W: Weight> DistanceOracle for [Vec<W>] {
+    type Weight = W;
+
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn distance(&self, from: usize, to: usize) -> W {
+        self[from][to]
+    }
+}
+
+impl<W: Weight> DistanceOracle for Vec<Vec<W>> {
+    type Weight = W;
+
+    fn size(&self) -> usize {
+        self.len()
+    }
+
+    fn distance(&self, from: usize, to: usize) -> W {
+        self[from][to]
+    }
+}
+
+/// Distances calculated on demand from city coordinates of type `P`
+pub struct LazyDistances<'a, M, P = (f64, f64)> {
+    cities: &'a [P],
+    metric: M,
+    neighbours: Vec<Vec<usize>>,
+}
+
+impl<'a, P, M: Metric<P>> LazyDistances<'a, M, P> {
+    /// Returns a `LazyDistances` measuring between `cities` using `metric`
+    pub fn new(cities: &'a [P], metric: M) -> LazyDistances<'a, M, P> {
+        LazyDistances {
+            cities,
+            metric,
+            neighbours: vec![],
+        }
+    }
+
+    /// Caches the `count` nearest neighbours of every city
+    ///
+    /// **Note: This compares every pair of cities, so takes time quadratic in the number of
+    /// cities. For very large instances, calculate the neighbours with a spatial index and pass
+    /// them to `with_neighbour_lists` instead.**
+    pub fn with_neighbours(mut self, count: usize) -> LazyDistances<'a, M, P> {
+        let count = count.min(self.cities.len().saturating_sub(1));
+
+        if count == 0 {
+            return self;
+        }
+
+        self.neighbours = (0..self.cities.len())
+            .map(|city| {
+                let mut nearest: Vec<(f64, usize)> = Vec::with_capacity(count + 1);
+
+                for other in (0..self.cities.len()).filter(|&other| other != city) {
+                    let distance = self.distance(city, other);
+
+                    if nearest.len() == count && distance >= nearest[count - 1].0 {
+                        continue;
+                    }
+
+                    let position = nearest.partition_point(|&(d, _)| d <= distance);
+                    nearest.insert(position, (distance, other));
+                    nearest.truncate(count);
+                }
+
+                nearest.into_iter().map(|(_, other)| other).collect()
+            })
+            .collect();
+
+        self
+    }
+
+    /// Uses the supplied nearest neighbours of every city, closest first
+    pub fn with_neighbour_lists(mut self, neighbours: Vec<Vec<usize>>) -> LazyDistances<'a, M, P> {
+        self.neighbours = neighbours;
+        self
+    }
+}
+
+impl<'a, P, M: Metric<P>> DistanceOracle for LazyDistances<'a, M, P> {
+    type Weight = f64;
+
+    fn size(&self) -> usize {
+        self.cities.len()
+    }
+
+    fn distance(&self, from: usize, to: usize) -> f64 {
+        self.metric.distance(&self.cities[from], &self.cities[to])
+    }
+
+    fn neighbours(&self, city: usize) -> Option<&[usize]> {
+        self.neighbours
+            .get(city)
+            .filter(|neighbours| !neighbours.is_empty())
+            .map(|neighbours| neighbours.as_slice())
+    }
+
+    fn symmetric(&self) -> bool {
+        true
+    }
+}
+
+/// The distances between a subset of cities, renumbered from zero in the order given
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::oracle::{DistanceOracle, Subset};
+///
+///fn main() {
+///  let distance_matrix = vec![
+///    vec![0.0, 3.0, 4.0],
+///    vec![3.0, 0.0, 5.0],
+///    vec![4.0, 5.0, 0.0],
+///  ];
+///
+///  let subset = Subset::new(&distance_matrix, &[2, 1]);
+///
+///  assert_eq!(subset.size(), 2);
+///  assert_eq!(subset.distance(0, 1), 5.0);
+///}
+///```
+pub struct Subset<'a, D: DistanceOracle + ?Sized> {
+    distances: &'a D,
+    cities: &'a [usize],
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Subset<'a, D> {
+    /// Returns the distances between `cities`, where city `i` of the subset is `cities[i]`
+    pub fn new(distances: &'a D, cities: &'a [usize]) -> Subset<'a, D> {
+        Subset { distances, cities }
+    }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> DistanceOracle for Subset<'a, D> {
+    type Weight = D::Weight;
+
+    fn size(&self) -> usize {
+        self.cities.len()
+    }
+
+    fn distance(&self, from: usize, to: usize) -> D::Weight {
+        self.distances.distance(self.cities[from], self.cities[to])
+    }
+
+    fn symmetric(&self) -> bool {
+        self.distances.symmetric()
+    }
+}
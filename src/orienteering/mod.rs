@@ -0,0 +1,216 @@
+//! Find an approximate solution to the Orienteering Problem
+//!
+//! Each city has a prize, which is collected by visiting it. The salesman starts and finishes at
+//! the depot, and collects as much prize as possible without travelling further than a budget.
+//! Not every city needs to be visited.
+//!
+//! Routes are grown and shrunk by inserting and dropping cities, improved by replacing cities and
+//! reversing sections, and perturbed by dropping cities at random whenever no move improves them.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (0.0, 0.0),
+//!    (10.0, 0.0),
+//!    (10.0, 10.0),
+//!    (0.0, 10.0),
+//!    (100.0, 100.0),
+//!  ];
+//!
+//!  let prize_tour = travelling_salesman::orienteering::solve(
+//!    &travelling_salesman::get_distance_matrix(&cities),
+//!    &[0.0, 1.0, 1.0, 1.0, 10.0],
+//!    50.0,
+//!    0,
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  assert_eq!(prize_tour.prize, 3.0);
+//!  assert_eq!(prize_tour.skipped, vec![4]);
+//!
+//!  println!(
+//!    "Route: {:?}, distance: {}",
+//!    prize_tour.tour.route, prize_tour.tour.distance,
+//!  );
+//!}
+//!```
+//!
+use time::Duration;
+
+use super::oracle::DistanceOracle;
+use super::selection::{prize_tour, search, validate, Goal};
+use super::{Error, Options, Tour};
+
+/// Represents a tour visiting only some of the cities
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PrizeTour<W = f64> {
+    /// the tour, which starts and finishes at the depot
+    pub tour: Tour<W>,
+    /// the total prize of the cities visited, excluding the depot
+    pub prize: f64,
+    /// the cities not visited, in ascending order
+    pub skipped: Vec<usize>,
+}
+
+/// Returns an approximate solution to the Orienteering Problem
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `prizes` is an array slice, containing the prize of each city. The prize of the depot is
+/// ignored.
+///
+/// `budget` is the longest distance the tour may travel.
+///
+/// `depot` is the city the tour starts and finishes at.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::orienteering::PrizeTour` struct, containing the tour, the
+/// prize collected and the cities skipped. Returns `travelling_salesman::Error::InvalidOptions` if
+/// the budget is negative.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let distances = vec![
+///    vec![0.0, 5.0, 5.0, 30.0],
+///    vec![5.0, 0.0, 5.0, 30.0],
+///    vec![5.0, 5.0, 0.0, 30.0],
+///    vec![30.0, 30.0, 30.0, 0.0],
+///  ];
+///
+///  let prize_tour = travelling_salesman::orienteering::solve(
+///    &distances,
+///    &[0.0, 2.0, 2.0, 5.0],
+///    60.0,
+///    0,
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(prize_tour.tour.route, vec![0, 3, 0]);
+///  assert_eq!(prize_tour.skipped, vec![1, 2]);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    prizes: &[f64],
+    budget: f64,
+    depot: usize,
+    runtime: Duration,
+) -> Result<PrizeTour<D::Weight>, Error> {
+    solve_with_options(
+        distances,
+        prizes,
+        budget,
+        depot,
+        runtime,
+        &Options::default(),
+    )
+}
+
+/// Returns an approximate solution to the Orienteering Problem, seeded and limited by the
+/// specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `prizes` is an array slice, containing the prize of each city. The prize of the depot is
+/// ignored.
+///
+/// `budget` is the longest distance the tour may travel.
+///
+/// `depot` is the city the tour starts and finishes at.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::orienteering::PrizeTour` struct, containing the tour, the
+/// prize collected and the cities skipped. Returns `travelling_salesman::Error::InvalidOptions` if
+/// the budget is negative.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(20, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let prizes: Vec<f64> = (0..20).map(|city| (city % 5) as f64).collect();
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::orienteering::solve_with_options(
+///      &distance_matrix,
+///      &prizes,
+///      200.0,
+///      0,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    prizes: &[f64],
+    budget: f64,
+    depot: usize,
+    runtime: Duration,
+    options: &Options,
+) -> Result<PrizeTour<D::Weight>, Error> {
+    options.validate_search_only()?;
+
+    validate(distances.size(), prizes, depot)?;
+
+    if budget < 0.0 {
+        return Err(Error::InvalidOptions(format!(
+            "the budget must not be negative, but is {}",
+            budget
+        )));
+    }
+
+    let mut prizes = prizes.to_vec();
+    prizes[depot] = 0.0;
+
+    let cities = search(
+        distances,
+        &prizes,
+        depot,
+        Goal::Budget(budget),
+        runtime,
+        options,
+    );
+
+    Ok(prize_tour(distances, &prizes, depot, cities))
+}
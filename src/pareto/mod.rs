@@ -0,0 +1,553 @@
+//! Find an approximate Pareto front for the multi-objective Travelling Salesman Problem
+//!
+//! Each objective, such as distance, travel time or toll cost, has its own matrix. No single tour
+//! is best for every objective, so the solver returns the front of tours which are not dominated,
+//! where one tour dominates another if it costs no more under every objective, and less under at
+//! least one.
+//!
+//! The front is seeded by hill climbing on weighted sums of the matrices, then grown using Pareto
+//! local search, which explores every reversal of a section of each tour on the front, keeping
+//! those which are not dominated. The quality of a front can be measured by its hypervolume.
+//!
+//! The front can grow very large, so it is limited to a capacity. When it is full, the tour in the
+//! most crowded region of the front is dropped, keeping the tours which best cover it, as in
+//! NSGA-II.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (27.0, 78.0),
+//!    (18.0, 24.0),
+//!    (48.0, 62.0),
+//!    (83.0, 77.0),
+//!    (55.0, 56.0),
+//!  ];
+//!
+//!  let distances = travelling_salesman::get_distance_matrix(&cities);
+//!  let tolls = vec![
+//!    vec![0.0, 5.0, 1.0, 1.0, 1.0],
+//!    vec![5.0, 0.0, 5.0, 1.0, 1.0],
+//!    vec![1.0, 5.0, 0.0, 5.0, 1.0],
+//!    vec![1.0, 1.0, 5.0, 0.0, 5.0],
+//!    vec![1.0, 1.0, 1.0, 5.0, 0.0],
+//!  ];
+//!
+//!  let front =
+//!    travelling_salesman::pareto::solve(&[&distances, &tolls], time::Duration::seconds(1)).unwrap();
+//!
+//!  for pareto_tour in &front {
+//!    println!("Costs: {:?}, route: {:?}", pareto_tour.costs, pareto_tour.tour.route);
+//!  }
+//!
+//!  println!(
+//!    "Hypervolume: {}",
+//!    travelling_salesman::pareto::hypervolume(&front, &[400.0, 25.0]),
+//!  );
+//!}
+//!```
+//!
+use rand::Rng;
+use std::time::Instant;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::{get_route_distance, hill_climbing, Error, Options, Tour};
+
+/// Represents a tour on the Pareto front, with its cost under every objective
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParetoTour<W = f64> {
+    /// the tour, where the distance is measured using the first matrix
+    pub tour: Tour<W>,
+    /// the cost of the tour measured using each matrix, in order
+    pub costs: Vec<W>,
+}
+
+/// The sum of several matrices, each multiplied by a weight
+struct WeightedSum<'a, D: DistanceOracle + ?Sized> {
+    matrices: &'a [&'a D],
+    weights: Vec<f64>,
+}
+
+impl<'a, D: DistanceOracle + ?Sized> DistanceOracle for WeightedSum<'a, D> {
+    type Weight = f64;
+
+    fn size(&self) -> usize {
+        self.matrices[0].size()
+    }
+
+    fn distance(&self, from: usize, to: usize) -> f64 {
+        self.matrices
+            .iter()
+            .zip(&self.weights)
+            .map(|(matrix, weight)| matrix.distance(from, to).to_f64() * weight)
+            .sum()
+    }
+}
+
+/// A tour on the front, and whether its neighbours have been explored
+struct Member {
+    route: Vec<usize>,
+    costs: Vec<f64>,
+    explored: bool,
+}
+
+/// Returns whether `costs` are no more than `other` for every objective, and less for at least
+/// one
+fn dominates(costs: &[f64], other: &[f64]) -> bool {
+    costs.iter().zip(other).all(|(cost, other)| cost <= other)
+        && costs.iter().zip(other).any(|(cost, other)| cost < other)
+}
+
+/// Adds `route` to `front` unless it is dominated by, or costs the same as, a member already on
+/// it, removing any members it dominates
+///
+/// When the front grows beyond `capacity`, the most crowded member is removed, which may be the
+/// route just added.
+fn add_to_front(front: &mut Vec<Member>, route: Vec<usize>, costs: Vec<f64>, capacity: usize) {
+    if front
+        .iter()
+        .any(|member| member.costs == costs || dominates(&member.costs, &costs))
+    {
+        return;
+    }
+
+    front.retain(|member| !dominates(&costs, &member.costs));
+    front.push(Member {
+        route,
+        costs,
+        explored: false,
+    });
+
+    if front.len() > capacity {
+        let crowding = crowding_distances(front);
+
+        let most_crowded = (0..front.len())
+            .min_by(|&a, &b| {
+                crowding[a]
+                    .partial_cmp(&crowding[b])
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .unwrap_or(0);
+
+        front.swap_remove(most_crowded);
+    }
+}
+
+/// Returns the crowding distance of each member of the front, which is the sum over every
+/// objective of the gap between its neighbours, relative to the range of the front
+///
+/// The members with the lowest and highest cost under any objective are never crowded.
+fn crowding_distances(front: &[Member]) -> Vec<f64> {
+    let mut crowding = vec![0.0; front.len()];
+    let mut order: Vec<usize> = (0..front.len()).collect();
+
+    for objective in 0..front[0].costs.len() {
+        let cost = |index: usize| front[index].costs[objective];
+
+        order.sort_by(|&a, &b| {
+            cost(a)
+                .partial_cmp(&cost(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let first = order[0];
+        let last = order[order.len() - 1];
+        let range = cost(last) - cost(first);
+
+        crowding[first] = f64::INFINITY;
+        crowding[last] = f64::INFINITY;
+
+        if range > 0.0 {
+            for window in order.windows(3) {
+                crowding[window[1]] += (cost(window[2]) - cost(window[0])) / range;
+            }
+        }
+    }
+
+    crowding
+}
+
+/// Returns an approximate Pareto front for the multi-objective Travelling Salesman Problem, of at
+/// most 100 tours
+///
+///# Parameters and Return Type
+///
+/// `matrices` is an array slice of `travelling_salesman::oracle::DistanceOracle`s, such as
+/// distance matrices, with one for each objective. Every matrix must have the same size.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `Vec` of `travelling_salesman::pareto::ParetoTour` structs, none of which dominates
+/// another, in ascending order of their cost under the first matrix. Returns
+/// `travelling_salesman::Error::InvalidOptions` if there are no matrices, or their sizes differ.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let distances = vec![
+///    vec![0.0, 1.0, 2.0, 1.0],
+///    vec![1.0, 0.0, 1.0, 2.0],
+///    vec![2.0, 1.0, 0.0, 1.0],
+///    vec![1.0, 2.0, 1.0, 0.0],
+///  ];
+///  let tolls = vec![
+///    vec![0.0, 9.0, 0.0, 0.0],
+///    vec![9.0, 0.0, 0.0, 0.0],
+///    vec![0.0, 0.0, 0.0, 0.0],
+///    vec![0.0, 0.0, 0.0, 0.0],
+///  ];
+///
+///  let front = travelling_salesman::pareto::solve(
+///    &[&distances, &tolls],
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  let costs: Vec<Vec<f64>> = front.iter().map(|pareto_tour| pareto_tour.costs.clone()).collect();
+///
+///  assert_eq!(costs, vec![vec![4.0, 9.0], vec![6.0, 0.0]]);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    matrices: &[&D],
+    runtime: Duration,
+) -> Result<Vec<ParetoTour<D::Weight>>, Error> {
+    solve_with_capacity(matrices, 100, runtime)
+}
+
+/// Returns an approximate Pareto front for the multi-objective Travelling Salesman Problem, of at
+/// most `capacity` tours
+///
+///# Parameters and Return Type
+///
+/// `matrices` is an array slice of `travelling_salesman::oracle::DistanceOracle`s, such as
+/// distance matrices, with one for each objective. Every matrix must have the same size.
+///
+/// `capacity` is the largest number of tours kept on the front.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `Vec` of `travelling_salesman::pareto::ParetoTour` structs, as `solve` does. Returns
+/// `travelling_salesman::Error::InvalidOptions` if there are no matrices, their sizes differ, or
+/// the capacity is zero.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(30, 100.0, 7);
+///  let distances = travelling_salesman::get_distance_matrix(&cities);
+///  let tolls = travelling_salesman::generators::asymmetric(30, 10.0, 7);
+///
+///  let front = travelling_salesman::pareto::solve_with_capacity(
+///    &[&distances, &tolls],
+///    5,
+///    time::Duration::milliseconds(500),
+///  )
+///  .unwrap();
+///
+///  assert!(!front.is_empty() && front.len() <= 5);
+///}
+///```
+pub fn solve_with_capacity<D: DistanceOracle + ?Sized>(
+    matrices: &[&D],
+    capacity: usize,
+    runtime: Duration,
+) -> Result<Vec<ParetoTour<D::Weight>>, Error> {
+    solve_with_options(matrices, capacity, runtime, &Options::default())
+}
+
+/// Returns an approximate Pareto front for the multi-objective Travelling Salesman Problem, of at
+/// most `capacity` tours, seeded and limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `matrices` is an array slice of `travelling_salesman::oracle::DistanceOracle`s, such as
+/// distance matrices, with one for each objective. Every matrix must have the same size.
+///
+/// `capacity` is the largest number of tours kept on the front.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations of each stage of the search instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `Vec` of `travelling_salesman::pareto::ParetoTour` structs, as `solve` does. Returns
+/// `travelling_salesman::Error::InvalidOptions` if there are no matrices, their sizes differ, or
+/// the capacity is zero.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(20, 100.0, 7);
+///  let distances = travelling_salesman::get_distance_matrix(&cities);
+///  let tolls = travelling_salesman::generators::asymmetric(20, 10.0, 7);
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::pareto::solve_with_options(
+///      &[&distances, &tolls],
+///      10,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    matrices: &[&D],
+    capacity: usize,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Vec<ParetoTour<D::Weight>>, Error> {
+    options.validate_search_only()?;
+
+    if capacity == 0 {
+        return Err(Error::InvalidOptions(
+            "the capacity of the front must be at least one".to_string(),
+        ));
+    }
+
+    if matrices.is_empty() {
+        return Err(Error::InvalidOptions(
+            "there must be at least one matrix".to_string(),
+        ));
+    }
+
+    let size = matrices[0].size();
+
+    if let Some(matrix) = matrices.iter().find(|matrix| matrix.size() != size) {
+        return Err(Error::InvalidOptions(format!(
+            "every matrix must have {} cities, but one has {}",
+            size,
+            matrix.size()
+        )));
+    }
+
+    let costs = |route: &[usize]| -> Vec<f64> {
+        matrices
+            .iter()
+            .map(|matrix| get_route_distance(*matrix, route).to_f64())
+            .collect()
+    };
+
+    let start_time = Instant::now();
+    let mut front = vec![];
+
+    // seed the front with the best tour for each objective alone, and for all of them equally
+
+    let objectives = matrices.len();
+    let mut weightings: Vec<Vec<f64>> = (0..objectives)
+        .map(|objective| {
+            (0..objectives)
+                .map(|other| if other == objective { 1.0 } else { 0.0 })
+                .collect()
+        })
+        .collect();
+
+    if objectives > 1 {
+        weightings.push(vec![1.0 / objectives as f64; objectives]);
+    }
+
+    let seed_runtime = runtime / 4 / weightings.len() as u32;
+    let mut rng = options.rng();
+
+    for weights in weightings {
+        let weighted_sum = WeightedSum { matrices, weights };
+        let seed_options = Options {
+            seed: Some(rng.gen()),
+            iterations: options.iterations,
+            ..Options::default()
+        };
+        let route =
+            hill_climbing::solve_with_options(&weighted_sum, seed_runtime, &seed_options)?.route;
+        let route_costs = costs(&route);
+
+        add_to_front(&mut front, route, route_costs, capacity);
+    }
+
+    // explore the reversals of every section of each tour on the front
+
+    let mut budget = options.budget(runtime - start_time.elapsed());
+
+    while budget.next() {
+        let index = match front.iter().position(|member| !member.explored) {
+            Some(index) => index,
+            None => break,
+        };
+
+        front[index].explored = true;
+        let route = front[index].route.clone();
+
+        // the first city of the closed route stays in place
+
+        for start in 1..route.len().saturating_sub(2) {
+            if !budget.next() {
+                break;
+            }
+
+            for end in (start + 1)..(route.len() - 1) {
+                let mut neighbour = route.clone();
+                neighbour[start..=end].reverse();
+                let neighbour_costs = costs(&neighbour);
+
+                add_to_front(&mut front, neighbour, neighbour_costs, capacity);
+            }
+        }
+    }
+
+    let mut pareto_tours: Vec<ParetoTour<D::Weight>> = front
+        .into_iter()
+        .map(|member| ParetoTour {
+            costs: matrices
+                .iter()
+                .map(|matrix| get_route_distance(*matrix, &member.route))
+                .collect(),
+            tour: Tour {
+                distance: get_route_distance(matrices[0], &member.route),
+                route: member.route,
+                closed: true,
+            },
+        })
+        .collect();
+
+    pareto_tours.sort_by(|a, b| {
+        a.tour
+            .distance
+            .partial_cmp(&b.tour.distance)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(pareto_tours)
+}
+
+/// Returns the hypervolume of a Pareto front, measuring how much of the space of costs it
+/// dominates
+///
+/// `front` is an array slice of `travelling_salesman::pareto::ParetoTour` structs.
+///
+/// `reference` is an array slice, containing a cost for each objective which is worse than any
+/// tour of interest. Tours which do not cost less than the reference under every objective add
+/// nothing.
+///
+/// Returns the volume of the region dominated by the front, and bounded by the reference. A larger
+/// hypervolume means a better front.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::pareto::ParetoTour;
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let pareto_tour = |costs: Vec<f64>| ParetoTour {
+///    tour: Tour {
+///      distance: costs[0],
+///      route: vec![],
+///      closed: true,
+///    },
+///    costs,
+///  };
+///
+///  let front = [pareto_tour(vec![1.0, 3.0]), pareto_tour(vec![2.0, 1.0])];
+///
+///  assert_eq!(travelling_salesman::pareto::hypervolume(&front, &[4.0, 4.0]), 7.0);
+///}
+///```
+pub fn hypervolume<W: Weight>(front: &[ParetoTour<W>], reference: &[f64]) -> f64 {
+    let points: Vec<Vec<f64>> = front
+        .iter()
+        .map(|pareto_tour| pareto_tour.costs.iter().map(|cost| cost.to_f64()).collect())
+        .collect();
+
+    slice_volume(&points, reference)
+}
+
+/// Returns the hypervolume of `points`, by slicing along the last objective and summing the
+/// volume of each slice from the points below it
+fn slice_volume(points: &[Vec<f64>], reference: &[f64]) -> f64 {
+    let dimensions = reference.len();
+
+    let mut points: Vec<&Vec<f64>> = points
+        .iter()
+        .filter(|point| {
+            point.len() >= dimensions
+                && point
+                    .iter()
+                    .zip(reference)
+                    .all(|(cost, limit)| cost < limit)
+        })
+        .collect();
+
+    if points.is_empty() || dimensions == 0 {
+        return 0.0;
+    }
+
+    if dimensions == 1 {
+        return points
+            .iter()
+            .map(|point| reference[0] - point[0])
+            .fold(0.0, f64::max);
+    }
+
+    let last = dimensions - 1;
+
+    points.sort_by(|a, b| {
+        a[last]
+            .partial_cmp(&b[last])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut volume = 0.0;
+
+    for index in 0..points.len() {
+        let top = match points.get(index + 1) {
+            Some(next) => next[last],
+            None => reference[last],
+        };
+        let height = top - points[index][last];
+
+        if height > 0.0 {
+            let below: Vec<Vec<f64>> = points[..=index]
+                .iter()
+                .map(|point| point[..last].to_vec())
+                .collect();
+
+            volume += height * slice_volume(&below, &reference[..last]);
+        }
+    }
+
+    volume
+}
@@ -0,0 +1,546 @@
+//! Find an approximate solution to the Travelling Salesman Problem with precedence constraints
+//!
+//! Some cities must be visited before others, such as collecting a key before opening a door.
+//! Shipments pair a pickup with a delivery, which must come after it, and carry a load between
+//! them. If the vehicle has a capacity, the load it carries must never exceed it.
+//!
+//! The route starts from the depot with the cities in an order respecting every precedence,
+//! visiting the nearest city allowed next, and keeping within the capacity where it can. It is then
+//! improved using simulated annealing, where each tweak relocates, swaps or reverses cities, or
+//! relocates a whole shipment. Tweaks breaking more constraints than before are rejected, and each
+//! broken constraint is penalised, so the search repairs a route which exceeds the capacity.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::precedence::Shipment;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (0.0, 0.0),
+//!    (10.0, 0.0),
+//!    (20.0, 0.0),
+//!    (20.0, 10.0),
+//!    (10.0, 10.0),
+//!  ];
+//!
+//!  let shipments = [
+//!    Shipment {
+//!      pickup: 4,
+//!      delivery: 1,
+//!      load: 1,
+//!    },
+//!    Shipment {
+//!      pickup: 3,
+//!      delivery: 2,
+//!      load: 1,
+//!    },
+//!  ];
+//!
+//!  let tour = travelling_salesman::precedence::solve(
+//!    &travelling_salesman::get_distance_matrix(&cities),
+//!    0,
+//!    &[],
+//!    &shipments,
+//!    Some(1),
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+//!}
+//!```
+//!
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::search::{self, Metaheuristics};
+use super::{get_route_distance, validate_city, Error, Options, Tour};
+
+/// The number of random tweaks tried before giving up on finding one which breaks no more
+/// constraints than the route it tweaks
+const TWEAK_ATTEMPTS: usize = 20;
+
+/// A load picked up at one city and delivered to another
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Shipment {
+    /// the city the load is collected from
+    pub pickup: usize,
+    /// the city the load is delivered to, which must be visited after the pickup
+    pub delivery: usize,
+    /// the size of the load, counting towards the vehicle capacity until it is delivered
+    pub load: u64,
+}
+
+struct ConstrainedSalesman<'a, D: DistanceOracle + ?Sized> {
+    distances: &'a D,
+    depot: usize,
+    precedences: Vec<(usize, usize)>,
+    shipments: &'a [Shipment],
+    load_changes: Vec<i64>,
+    capacity: Option<u64>,
+    penalty: f64,
+    rng: &'a mut StdRng,
+}
+
+struct Candidate {
+    route: Vec<usize>,
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Metaheuristics<Candidate> for ConstrainedSalesman<'a, D> {
+    fn clone_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        Candidate {
+            route: candidate.route.clone(),
+        }
+    }
+
+    fn generate_candidate(&mut self) -> Candidate {
+        Candidate {
+            route: self.nearest_allowed_route(),
+        }
+    }
+
+    fn rank_candidate(&mut self, candidate: &Candidate) -> f64 {
+        let violations = self.violations(&candidate.route) as f64;
+
+        0.0 - get_route_distance(self.distances, &candidate.route).to_f64()
+            - violations * self.penalty
+    }
+
+    fn tweak_candidate(&mut self, candidate: &Candidate) -> Candidate {
+        // the depot at either end of the route stays in place
+
+        let last = candidate.route.len() - 1;
+
+        if last < 3 {
+            return self.clone_candidate(candidate);
+        }
+
+        let violations = self.violations(&candidate.route);
+
+        for _ in 0..TWEAK_ATTEMPTS {
+            let mut route = candidate.route.clone();
+            let first = self.rng.gen_range(1..last);
+            let second = self.rng.gen_range(1..last);
+
+            match self.rng.gen_range(0..4) {
+                0 => {
+                    // move a city to another position
+
+                    let city = route.remove(first);
+                    route.insert(second, city);
+                }
+                1 => {
+                    // swap two cities
+
+                    route.swap(first, second);
+                }
+                2 => {
+                    // reverse the cities between two positions
+
+                    if first < second {
+                        route[first..=second].reverse();
+                    } else {
+                        route[second..=first].reverse();
+                    }
+                }
+                _ => {
+                    // move a shipment, keeping its delivery after its pickup
+
+                    if self.shipments.is_empty() {
+                        continue;
+                    }
+
+                    let shipment = self.shipments[self.rng.gen_range(0..self.shipments.len())];
+                    route.retain(|&city| city != shipment.pickup && city != shipment.delivery);
+
+                    let (pickup, delivery) = if first < second {
+                        (first, second)
+                    } else {
+                        (second, first)
+                    };
+
+                    let pickup = pickup.min(route.len() - 1);
+                    route.insert(pickup, shipment.pickup);
+
+                    let delivery = delivery.clamp(pickup + 1, route.len() - 1);
+                    route.insert(delivery, shipment.delivery);
+                }
+            }
+
+            if self.violations(&route) <= violations {
+                return Candidate { route };
+            }
+        }
+
+        self.clone_candidate(candidate)
+    }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> ConstrainedSalesman<'a, D> {
+    /// Returns the number of cities `route` visits before a city which must come before them,
+    /// plus the number of cities it leaves carrying more than the capacity
+    ///
+    /// A route keeps every constraint when this is zero.
+    fn violations(&self, route: &[usize]) -> usize {
+        let mut positions = vec![0; self.distances.size()];
+
+        for (position, &city) in route.iter().enumerate() {
+            positions[city] = position;
+        }
+
+        let out_of_order = self
+            .precedences
+            .iter()
+            .filter(|&&(before, after)| positions[before] > positions[after])
+            .count();
+
+        let overloaded = match self.capacity {
+            Some(capacity) => {
+                let mut load = 0;
+
+                route
+                    .iter()
+                    .filter(|&&city| {
+                        load += self.load_changes[city];
+                        load > capacity as i64
+                    })
+                    .count()
+            }
+            None => 0,
+        };
+
+        out_of_order + overloaded
+    }
+
+    /// Returns a closed route from the depot, visiting the nearest city allowed next each time
+    ///
+    /// A city is allowed once every city which must come before it has been visited, and its
+    /// pickup fits in the vehicle. If no city fits, the nearest city otherwise allowed is visited,
+    /// leaving the search to bring the load back within the capacity.
+    fn nearest_allowed_route(&self) -> Vec<usize> {
+        let size = self.distances.size();
+        let mut waiting_for = vec![0; size];
+
+        for &(_, after) in &self.precedences {
+            waiting_for[after] += 1;
+        }
+
+        let mut visited = vec![false; size];
+        visited[self.depot] = true;
+
+        let mut route = vec![self.depot];
+        let mut load = 0;
+
+        while route.len() < size {
+            let current = route[route.len() - 1];
+            let distance = |city: usize| self.distances.distance(current, city).to_f64();
+
+            let nearest = |fits: &dyn Fn(usize) -> bool| {
+                (0..size)
+                    .filter(|&city| !visited[city] && waiting_for[city] == 0 && fits(city))
+                    .min_by(|&a, &b| {
+                        distance(a)
+                            .partial_cmp(&distance(b))
+                            .unwrap_or(std::cmp::Ordering::Equal)
+                    })
+            };
+
+            let next = nearest(&|city| {
+                self.capacity.map_or(true, |capacity| {
+                    load + self.load_changes[city] <= capacity as i64
+                })
+            })
+            .or_else(|| nearest(&|_| true));
+
+            let next = match next {
+                Some(next) => next,
+                None => break,
+            };
+
+            for &(_, after) in self
+                .precedences
+                .iter()
+                .filter(|&&(before, _)| before == next)
+            {
+                waiting_for[after] -= 1;
+            }
+
+            visited[next] = true;
+            load += self.load_changes[next];
+            route.push(next);
+        }
+
+        route.push(self.depot);
+        route
+    }
+}
+
+/// Returns whether the cities in `precedences` can be ordered, so that no city must come both
+/// before and after another
+fn acyclic(size: usize, precedences: &[(usize, usize)]) -> bool {
+    let mut waiting_for = vec![0; size];
+
+    for &(_, after) in precedences {
+        waiting_for[after] += 1;
+    }
+
+    let mut ready: Vec<usize> = (0..size).filter(|&city| waiting_for[city] == 0).collect();
+    let mut ordered = 0;
+
+    while let Some(city) = ready.pop() {
+        ordered += 1;
+
+        for &(_, after) in precedences.iter().filter(|&&(before, _)| before == city) {
+            waiting_for[after] -= 1;
+
+            if waiting_for[after] == 0 {
+                ready.push(after);
+            }
+        }
+    }
+
+    ordered == size
+}
+
+/// Returns an approximate solution to the Travelling Salesman Problem with precedence constraints
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `depot` is the city the tour starts and finishes at.
+///
+/// `precedences` is an array slice of `(before, after)` tuples, where city `before` must be
+/// visited before city `after`.
+///
+/// `shipments` is an array slice of `travelling_salesman::precedence::Shipment` structs, each
+/// pairing a pickup with a later delivery.
+///
+/// `capacity` is the largest load the vehicle can carry, or `None` if it is unlimited.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::Tour` struct, starting and finishing at the depot. Returns
+/// `travelling_salesman::Error::InvalidOptions` if a constraint involves the depot, and
+/// `travelling_salesman::Error::Infeasible` if the constraints contradict each other, or no route
+/// within the capacity was found.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = [
+///    (0.0, 0.0),
+///    (10.0, 0.0),
+///    (20.0, 0.0),
+///    (30.0, 0.0),
+///  ];
+///
+///  let tour = travelling_salesman::precedence::solve(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    0,
+///    &[(3, 1)],
+///    &[],
+///    None,
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  let position = |city| tour.route.iter().position(|&c| c == city);
+///
+///  assert!(position(3) < position(1));
+///  assert_eq!(tour.distance, 60.0);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    depot: usize,
+    precedences: &[(usize, usize)],
+    shipments: &[Shipment],
+    capacity: Option<u64>,
+    runtime: Duration,
+) -> Result<Tour<D::Weight>, Error> {
+    solve_with_options(
+        distances,
+        depot,
+        precedences,
+        shipments,
+        capacity,
+        runtime,
+        &Options::default(),
+    )
+}
+
+/// Returns an approximate solution to the Travelling Salesman Problem with precedence constraints,
+/// seeded and limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `depot` is the city the tour starts and finishes at.
+///
+/// `precedences` is an array slice of `(before, after)` tuples, where city `before` must be
+/// visited before city `after`.
+///
+/// `shipments` is an array slice of `travelling_salesman::precedence::Shipment` structs, each
+/// pairing a pickup with a later delivery.
+///
+/// `capacity` is the largest load the vehicle can carry, or `None` if it is unlimited.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::Tour` struct, starting and finishing at the depot. Returns
+/// `travelling_salesman::Error::InvalidOptions` if a constraint involves the depot, and
+/// `travelling_salesman::Error::Infeasible` if the constraints contradict each other, or no route
+/// within the capacity was found.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::precedence::Shipment;
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(10, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let shipments = [
+///    Shipment { pickup: 1, delivery: 2, load: 3 },
+///    Shipment { pickup: 3, delivery: 4, load: 3 },
+///  ];
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(10_000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::precedence::solve_with_options(
+///      &distance_matrix,
+///      0,
+///      &[(5, 6)],
+///      &shipments,
+///      Some(5),
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    depot: usize,
+    precedences: &[(usize, usize)],
+    shipments: &[Shipment],
+    capacity: Option<u64>,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Tour<D::Weight>, Error> {
+    options.validate_search_only()?;
+
+    let size = distances.size();
+
+    validate_city(depot, size)?;
+
+    let mut all_precedences = precedences.to_vec();
+
+    for shipment in shipments {
+        all_precedences.push((shipment.pickup, shipment.delivery));
+
+        if capacity.is_some_and(|capacity| shipment.load > capacity) {
+            return Err(Error::Infeasible(format!(
+                "the load from city {} exceeds the vehicle capacity",
+                shipment.pickup
+            )));
+        }
+    }
+
+    for &(before, after) in &all_precedences {
+        validate_city(before, size)?;
+        validate_city(after, size)?;
+
+        if before == depot || after == depot {
+            return Err(Error::InvalidOptions(format!(
+                "the depot {} cannot be constrained",
+                depot
+            )));
+        }
+    }
+
+    if !acyclic(size, &all_precedences) {
+        return Err(Error::Infeasible(
+            "the precedence constraints contain a cycle".to_string(),
+        ));
+    }
+
+    // the change in load on leaving each city
+
+    let mut load_changes = vec![0; size];
+
+    for shipment in shipments {
+        load_changes[shipment.pickup] += shipment.load as i64;
+        load_changes[shipment.delivery] -= shipment.load as i64;
+    }
+
+    // each broken constraint costs more than any route could travel, so the search prefers every
+    // route keeping the constraints to those which do not
+
+    let longest = (0..size)
+        .flat_map(|from| (0..size).map(move |to| (from, to)))
+        .map(|(from, to)| distances.distance(from, to).to_f64())
+        .fold(0.0, f64::max);
+
+    // annealing chooses which worse routes to accept using a generator of its own
+
+    let mut rng = options.rng();
+    let mut search_rng = StdRng::seed_from_u64(rng.gen());
+
+    let mut tsp = ConstrainedSalesman {
+        distances,
+        depot,
+        precedences: all_precedences,
+        shipments,
+        load_changes,
+        capacity,
+        penalty: longest * (size + 1) as f64,
+        rng: &mut rng,
+    };
+
+    let best_candidate =
+        search::simulated_annealing(&mut tsp, &mut options.budget(runtime), &mut search_rng);
+
+    if tsp.violations(&best_candidate.route) > 0 {
+        return Err(Error::Infeasible(
+            "no route within the vehicle capacity was found".to_string(),
+        ));
+    }
+
+    Ok(Tour {
+        distance: get_route_distance(distances, &best_candidate.route),
+        route: best_candidate.route,
+        closed: true,
+    })
+}
@@ -0,0 +1,180 @@
+//! Find an approximate solution to the Prize-Collecting Travelling Salesman Problem
+//!
+//! Each city has a penalty, which must be paid if the city is skipped. The salesman starts and
+//! finishes at the depot, and minimises the distance travelled plus the penalties of every city
+//! skipped, so cities are only visited when the detour costs less than their penalty.
+//!
+//! Routes are grown and shrunk by inserting and dropping cities, improved by replacing cities and
+//! reversing sections, and perturbed by dropping cities at random whenever no move improves them.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (0.0, 0.0),
+//!    (10.0, 0.0),
+//!    (10.0, 10.0),
+//!    (0.0, 10.0),
+//!    (100.0, 100.0),
+//!  ];
+//!
+//!  let prize_tour = travelling_salesman::prize_collecting::solve(
+//!    &travelling_salesman::get_distance_matrix(&cities),
+//!    &[0.0, 20.0, 20.0, 20.0, 50.0],
+//!    0,
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  assert_eq!(prize_tour.skipped, vec![4]);
+//!
+//!  println!(
+//!    "Route: {:?}, distance: {}, penalties avoided: {}",
+//!    prize_tour.tour.route, prize_tour.tour.distance, prize_tour.prize,
+//!  );
+//!}
+//!```
+//!
+use time::Duration;
+
+use super::oracle::DistanceOracle;
+use super::orienteering::PrizeTour;
+use super::selection::{prize_tour, search, validate, Goal};
+use super::{Error, Options};
+
+/// Returns an approximate solution to the Prize-Collecting Travelling Salesman Problem
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `penalties` is an array slice, containing the penalty for skipping each city. The penalty of
+/// the depot is ignored.
+///
+/// `depot` is the city the tour starts and finishes at.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::orienteering::PrizeTour` struct, containing the tour, the
+/// penalties avoided by visiting cities as the prize, and the cities skipped.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let distances = vec![
+///    vec![0.0, 5.0, 5.0, 30.0],
+///    vec![5.0, 0.0, 5.0, 30.0],
+///    vec![5.0, 5.0, 0.0, 30.0],
+///    vec![30.0, 30.0, 30.0, 0.0],
+///  ];
+///
+///  let prize_tour = travelling_salesman::prize_collecting::solve(
+///    &distances,
+///    &[0.0, 10.0, 10.0, 40.0],
+///    0,
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(prize_tour.tour.distance, 15.0);
+///  assert_eq!(prize_tour.skipped, vec![3]);
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    penalties: &[f64],
+    depot: usize,
+    runtime: Duration,
+) -> Result<PrizeTour<D::Weight>, Error> {
+    solve_with_options(distances, penalties, depot, runtime, &Options::default())
+}
+
+/// Returns an approximate solution to the Prize-Collecting Travelling Salesman Problem, seeded and
+/// limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `penalties` is an array slice, containing the penalty for skipping each city. The penalty of
+/// the depot is ignored.
+///
+/// `depot` is the city the tour starts and finishes at.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::orienteering::PrizeTour` struct, containing the tour, the
+/// penalties avoided by visiting cities as the prize, and the cities skipped.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(20, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let penalties: Vec<f64> = (0..20).map(|city| (city % 5 * 10) as f64).collect();
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::prize_collecting::solve_with_options(
+///      &distance_matrix,
+///      &penalties,
+///      0,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    penalties: &[f64],
+    depot: usize,
+    runtime: Duration,
+    options: &Options,
+) -> Result<PrizeTour<D::Weight>, Error> {
+    options.validate_search_only()?;
+
+    validate(distances.size(), penalties, depot)?;
+
+    let mut penalties = penalties.to_vec();
+    penalties[depot] = 0.0;
+
+    let total = penalties.iter().sum();
+    let cities = search(
+        distances,
+        &penalties,
+        depot,
+        Goal::Penalties(total),
+        runtime,
+        options,
+    );
+
+    Ok(prize_tour(distances, &penalties, depot, cities))
+}
@@ -1,8 +1,7 @@
 //! Find an approximate solution to the Travelling Salesman Problem using Random Search
 //!
-//! For more information, please see the
-//! [metaheuristics::random_search](https://www.alfie.wtf/rustdoc/metaheuristics/metaheuristics/random_search)
-//! documentation.
+//! The search generates random tours until its runtime is spent, and returns the best of them. It
+//! is mostly useful as a baseline to compare the other solvers against.
 //!
 //!# Examples
 //!
@@ -26,18 +25,16 @@
 //!}
 //!```
 //!
-extern crate metaheuristics;
-
-use rand::thread_rng;
 use time::Duration;
 
-use super::{get_distance_matrix, get_route_distance, Tour, TravellingSalesman};
+use super::metrics::{Euclidean, Metric};
+use super::oracle::DistanceOracle;
+use super::search;
+use super::{get_distance_matrix_with_metric, solve_with, Error, Options, Tour};
 
 /// Returns an approximate solution to the Travelling Salesman Problem using Random Search
 ///
-/// For more information, please see the
-/// [metaheuristics::random_search](https://www.alfie.wtf/rustdoc/metaheuristics/metaheuristics/random_search/)
-/// documentation.
+/// The search returns the best of the random tours it generates.
 ///
 ///# Parameters and Return Type
 ///
@@ -69,15 +66,154 @@ use super::{get_distance_matrix, get_route_distance, Tour, TravellingSalesman};
 ///}
 ///```
 pub fn solve(cities: &[(f64, f64)], runtime: Duration) -> Tour {
-    let mut tsp = TravellingSalesman {
-        distance_matrix: &get_distance_matrix(cities),
-        rng: &mut thread_rng(),
-    };
+    solve_with_metric(cities, &Euclidean::new(), runtime)
+}
 
-    let best_candidate = metaheuristics::random_search::solve(&mut tsp, runtime);
+/// Returns an approximate solution to the Travelling Salesman Problem using Random Search, measuring
+/// distances between cities with the specified metric
+///
+///# Parameters and Return Type
+///
+/// `cities` is an array slice, containing the coordinates for each city.
+///
+/// `metric` is a `travelling_salesman::metrics::Metric`, specifying how to measure the distance
+/// between two cities.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::Tour` struct, representing the approximate solution found.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::metrics::Haversine;
+///
+///fn main() {
+///  let tour = travelling_salesman::random_search::solve_with_metric(
+///    &[
+///       (-33.8688, 151.2093),
+///       (-37.8136, 144.9631),
+///       (-27.4698, 153.0251),
+///       (-34.9285, 138.6007),
+///       (-35.2809, 149.1300),
+///    ],
+///    &Haversine::kilometres(),
+///    time::Duration::seconds(1),
+///  );
+///
+///  println!("Tour distance: {}km, route: {:?}", tour.distance, tour.route);
+///}
+///```
+pub fn solve_with_metric<P, M: Metric<P>>(cities: &[P], metric: &M, runtime: Duration) -> Tour {
+    solve_with_oracle(&get_distance_matrix_with_metric(cities, metric), runtime)
+}
 
-    Tour {
-        distance: get_route_distance(tsp.distance_matrix, &best_candidate.route),
-        route: best_candidate.route,
-    }
+/// Returns an approximate solution to the Travelling Salesman Problem using Random Search, querying
+/// distances between cities from the specified oracle
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix or
+/// `travelling_salesman::oracle::LazyDistances`.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::Tour` struct, representing the approximate solution found, with
+/// a distance of the same type as the oracle's weights.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::metrics::Euclidean;
+///use travelling_salesman::oracle::LazyDistances;
+///
+///fn main() {
+///  let cities = [
+///    (27.0, 78.0),
+///    (18.0, 24.0),
+///    (48.0, 62.0),
+///    (83.0, 77.0),
+///    (55.0, 56.0),
+///  ];
+///
+///  let tour = travelling_salesman::random_search::solve_with_oracle(
+///    &LazyDistances::new(&cities, Euclidean::new()).with_neighbours(3),
+///    time::Duration::seconds(1),
+///  );
+///
+///  println!("Tour distance: {}, route: {:?}", tour.distance, tour.route);
+///}
+///```
+pub fn solve_with_oracle<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    runtime: Duration,
+) -> Tour<D::Weight> {
+    solve_with_options(distances, runtime, &Options::default())
+        .expect("default options are always valid")
+}
+
+/// Returns an approximate solution to the Travelling Salesman Problem using Random Search, searching
+/// for the kind of route described by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix or
+/// `travelling_salesman::oracle::LazyDistances`.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, specifying the kind of route to search for,
+/// the city it must start from, what makes one route better than another, any edges it must or
+/// must not travel along, and how the search is seeded and limited.
+///
+/// Returns a `travelling_salesman::Tour` struct, representing the approximate solution found, or a
+/// `travelling_salesman::Error` if the options are invalid for these distances, or no route keeping
+/// the fixed and forbidden edges was found.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::{Options, Shape};
+///
+///fn main() {
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&[
+///    (27.0, 78.0),
+///    (18.0, 24.0),
+///    (48.0, 62.0),
+///    (83.0, 77.0),
+///    (55.0, 56.0),
+///  ]);
+///
+///  let path = travelling_salesman::random_search::solve_with_options(
+///    &distance_matrix,
+///    time::Duration::seconds(1),
+///    &Options {
+///      shape: Shape::PathBetween(0, 3),
+///      ..Options::default()
+///    },
+///  )
+///  .unwrap();
+///
+///  assert!(!path.closed);
+///  println!("Path distance: {}, route: {:?}", path.distance, path.route);
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Tour<D::Weight>, Error> {
+    solve_with(distances, runtime, options, |tsp, budget, _| {
+        search::random_search(tsp, budget)
+    })
 }
@@ -0,0 +1,671 @@
+//! Draw cities and tours, to see crossing edges and badly routed regions
+//!
+//! Drawings are written as SVG, or rasterised to PNG when the `png` feature is enabled. City
+//! coordinates are `(x, y)` tuples, scaled to fit the image with `y` increasing upwards.
+//!
+//! Several tours can be drawn at once, such as the routes of each vehicle, each in its own colour.
+//! Cities can be coloured by cluster, the depot highlighted, and each city labelled with its
+//! index. Two tours can also be overlaid to show where they differ.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::render::Style;
+//!
+//!fn main() {
+//!  let cities = [
+//!    (27.0, 78.0),
+//!    (18.0, 24.0),
+//!    (48.0, 62.0),
+//!    (83.0, 77.0),
+//!    (55.0, 56.0),
+//!  ];
+//!
+//!  let tour = travelling_salesman::simulated_annealing::solve(&cities, time::Duration::seconds(1));
+//!
+//!  let style = Style {
+//!    depot: Some(0),
+//!    ..Style::default()
+//!  };
+//!
+//!  let svg = travelling_salesman::render::to_svg(&cities, &[tour], &style).unwrap();
+//!
+//!  println!("{}", svg);
+//!}
+//!```
+//!
+use std::collections::BTreeSet;
+
+use super::{validate_city, Error, Tour};
+
+/// How a drawing looks
+///
+/// SVG images also accept other colours, such as `red`, which are escaped before being written.
+/// PNG images only accept `#rrggbb` colours.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+    /// the width of the image, in pixels
+    pub width: f64,
+    /// the height of the image, in pixels
+    pub height: f64,
+    /// the space left around the cities, in pixels
+    pub margin: f64,
+    /// the radius of each city, in pixels
+    pub city_radius: f64,
+    /// the width of each edge, in pixels
+    pub line_width: f64,
+    /// whether to label each city with its index, which is only drawn in SVG
+    pub labels: bool,
+    /// the city to highlight as the depot, if any
+    pub depot: Option<usize>,
+    /// the cities of each cluster, coloured using `colours` in order
+    pub clusters: Vec<Vec<usize>>,
+    /// the `#rrggbb` colours used for each tour, or each cluster, in order and repeating
+    pub colours: Vec<String>,
+    /// the `#rrggbb` colour of cities which are not in any cluster, and of the depot outline
+    pub city_colour: String,
+    /// the `#rrggbb` colour of the background
+    pub background: String,
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style {
+            width: 800.0,
+            height: 800.0,
+            margin: 20.0,
+            city_radius: 3.0,
+            line_width: 1.5,
+            labels: false,
+            depot: None,
+            clusters: vec![],
+            colours: [
+                "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2",
+                "#17becf",
+            ]
+            .iter()
+            .map(|colour| colour.to_string())
+            .collect(),
+            city_colour: "#333333".to_string(),
+            background: "#ffffff".to_string(),
+        }
+    }
+}
+
+impl Style {
+    fn colour(&self, index: usize) -> &str {
+        if self.colours.is_empty() {
+            &self.city_colour
+        } else {
+            &self.colours[index % self.colours.len()]
+        }
+    }
+}
+
+/// A shape drawn in image coordinates
+enum Element {
+    Line {
+        from: (f64, f64),
+        to: (f64, f64),
+        colour: String,
+        width: f64,
+        dashed: bool,
+    },
+    Circle {
+        centre: (f64, f64),
+        radius: f64,
+        colour: String,
+        outline: Option<String>,
+    },
+    Label {
+        position: (f64, f64),
+        text: String,
+    },
+}
+
+/// The elements of an image, in the order they are drawn
+struct Drawing {
+    width: f64,
+    height: f64,
+    background: String,
+    elements: Vec<Element>,
+}
+
+/// Converts city coordinates to image coordinates
+struct Projection {
+    min_x: f64,
+    max_y: f64,
+    scale: f64,
+    offset: (f64, f64),
+}
+
+impl Projection {
+    /// Returns a projection fitting `cities` within the image, keeping their proportions
+    fn new(cities: &[(f64, f64)], style: &Style) -> Projection {
+        let (min_x, max_x, min_y, max_y) = cities.iter().fold(
+            (
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+                f64::INFINITY,
+                f64::NEG_INFINITY,
+            ),
+            |(min_x, max_x, min_y, max_y), &(x, y)| {
+                (min_x.min(x), max_x.max(x), min_y.min(y), max_y.max(y))
+            },
+        );
+
+        let inner_width = (style.width - 2.0 * style.margin).max(0.0);
+        let inner_height = (style.height - 2.0 * style.margin).max(0.0);
+        let span_x = (max_x - min_x).max(f64::EPSILON);
+        let span_y = (max_y - min_y).max(f64::EPSILON);
+        let scale = (inner_width / span_x).min(inner_height / span_y);
+
+        // centre the cities within the margins
+
+        let scale = if scale.is_finite() { scale } else { 1.0 };
+        let offset = (
+            style.margin + (inner_width - (max_x - min_x).max(0.0) * scale) / 2.0,
+            style.margin + (inner_height - (max_y - min_y).max(0.0) * scale) / 2.0,
+        );
+
+        Projection {
+            min_x,
+            max_y,
+            scale,
+            offset,
+        }
+    }
+
+    fn project(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (
+            self.offset.0 + (x - self.min_x) * self.scale,
+            self.offset.1 + (self.max_y - y) * self.scale,
+        )
+    }
+}
+
+impl Drawing {
+    /// Returns a drawing with the background, ready for edges to be added before the cities
+    fn new(style: &Style) -> Drawing {
+        Drawing {
+            width: style.width,
+            height: style.height,
+            background: style.background.clone(),
+            elements: vec![],
+        }
+    }
+
+    fn add_edge(
+        &mut self,
+        projection: &Projection,
+        cities: &[(f64, f64)],
+        (from, to): (usize, usize),
+        colour: &str,
+        style: &Style,
+        dashed: bool,
+    ) {
+        self.elements.push(Element::Line {
+            from: projection.project(cities[from]),
+            to: projection.project(cities[to]),
+            colour: colour.to_string(),
+            width: style.line_width,
+            dashed,
+        });
+    }
+
+    /// Adds every city, coloured by cluster, with the depot highlighted and labels if requested
+    fn add_cities(&mut self, projection: &Projection, cities: &[(f64, f64)], style: &Style) {
+        let mut colours = vec![style.city_colour.clone(); cities.len()];
+
+        for (index, cluster) in style.clusters.iter().enumerate() {
+            for &city in cluster.iter().filter(|&&city| city < cities.len()) {
+                colours[city] = style.colour(index).to_string();
+            }
+        }
+
+        for (city, &coordinates) in cities.iter().enumerate() {
+            let centre = projection.project(coordinates);
+            let depot = style.depot == Some(city);
+
+            self.elements.push(Element::Circle {
+                centre,
+                radius: if depot {
+                    style.city_radius * 2.0
+                } else {
+                    style.city_radius
+                },
+                colour: colours[city].clone(),
+                outline: if depot {
+                    Some(style.city_colour.clone())
+                } else {
+                    None
+                },
+            });
+
+            if style.labels {
+                self.elements.push(Element::Label {
+                    position: (
+                        centre.0 + style.city_radius + 2.0,
+                        centre.1 - style.city_radius,
+                    ),
+                    text: city.to_string(),
+                });
+            }
+        }
+    }
+
+    fn svg(&self) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n  <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+            self.width,
+            self.height,
+            self.width,
+            self.height,
+            escape(&self.background)
+        );
+
+        for element in &self.elements {
+            svg += &match *element {
+                Element::Line {
+                    from,
+                    to,
+                    ref colour,
+                    width,
+                    dashed,
+                } => format!(
+                    "  <line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"{}\"{}/>\n",
+                    from.0,
+                    from.1,
+                    to.0,
+                    to.1,
+                    escape(colour),
+                    width,
+                    if dashed {
+                        format!(" stroke-dasharray=\"{} {}\"", width * 4.0, width * 3.0)
+                    } else {
+                        String::new()
+                    }
+                ),
+                Element::Circle {
+                    centre,
+                    radius,
+                    ref colour,
+                    ref outline,
+                } => format!(
+                    "  <circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"{}\"{}/>\n",
+                    centre.0,
+                    centre.1,
+                    radius,
+                    escape(colour),
+                    match *outline {
+                        Some(ref outline) => {
+                            format!(" stroke=\"{}\" stroke-width=\"2\"", escape(outline))
+                        }
+                        None => String::new(),
+                    }
+                ),
+                Element::Label { position, ref text } => format!(
+                    "  <text x=\"{:.2}\" y=\"{:.2}\" font-family=\"sans-serif\" font-size=\"10\">{}</text>\n",
+                    position.0, position.1, text
+                ),
+            };
+        }
+
+        svg += "</svg>\n";
+        svg
+    }
+
+    #[cfg(feature = "png")]
+    fn png(&self) -> Result<Vec<u8>, Error> {
+        use tiny_skia::{FillRule, Paint, PathBuilder, Pixmap, Stroke, StrokeDash, Transform};
+
+        let mut pixmap = Pixmap::new(self.width.round() as u32, self.height.round() as u32)
+            .ok_or_else(|| {
+                Error::InvalidOptions(format!(
+                    "cannot draw an image of {} by {} pixels",
+                    self.width, self.height
+                ))
+            })?;
+
+        pixmap.fill(parse_colour(&self.background)?);
+
+        let paint = |colour: &str| -> Result<Paint, Error> {
+            let mut paint = Paint::default();
+            paint.set_color(parse_colour(colour)?);
+            paint.anti_alias = true;
+            Ok(paint)
+        };
+
+        for element in &self.elements {
+            match *element {
+                Element::Line {
+                    from,
+                    to,
+                    ref colour,
+                    width,
+                    dashed,
+                } => {
+                    let mut builder = PathBuilder::new();
+                    builder.move_to(from.0 as f32, from.1 as f32);
+                    builder.line_to(to.0 as f32, to.1 as f32);
+
+                    let mut stroke = Stroke {
+                        width: width as f32,
+                        ..Stroke::default()
+                    };
+
+                    if dashed {
+                        stroke.dash =
+                            StrokeDash::new(vec![width as f32 * 4.0, width as f32 * 3.0], 0.0);
+                    }
+
+                    if let Some(path) = builder.finish() {
+                        pixmap.stroke_path(
+                            &path,
+                            &paint(colour)?,
+                            &stroke,
+                            Transform::identity(),
+                            None,
+                        );
+                    }
+                }
+                Element::Circle {
+                    centre,
+                    radius,
+                    ref colour,
+                    ref outline,
+                } => {
+                    if let Some(path) =
+                        PathBuilder::from_circle(centre.0 as f32, centre.1 as f32, radius as f32)
+                    {
+                        pixmap.fill_path(
+                            &path,
+                            &paint(colour)?,
+                            FillRule::Winding,
+                            Transform::identity(),
+                            None,
+                        );
+
+                        if let Some(ref outline) = *outline {
+                            let stroke = Stroke {
+                                width: 2.0,
+                                ..Stroke::default()
+                            };
+
+                            pixmap.stroke_path(
+                                &path,
+                                &paint(outline)?,
+                                &stroke,
+                                Transform::identity(),
+                                None,
+                            );
+                        }
+                    }
+                }
+                // rasterising text would need a font, so labels are only drawn in SVG
+                Element::Label { .. } => {}
+            }
+        }
+
+        pixmap
+            .encode_png()
+            .map_err(|error| Error::Io(error.to_string()))
+    }
+}
+
+/// Returns `text` with the characters which would end an SVG attribute or element escaped
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+}
+
+/// Returns the colour of a `#rrggbb` string
+#[cfg(feature = "png")]
+fn parse_colour(colour: &str) -> Result<tiny_skia::Color, Error> {
+    let invalid = || Error::InvalidOptions(format!("invalid colour \"{}\"", colour));
+
+    let hex = colour.strip_prefix('#').ok_or_else(invalid)?;
+
+    if hex.len() != 6 || !hex.is_ascii() {
+        return Err(invalid());
+    }
+
+    let channel =
+        |start: usize| u8::from_str_radix(&hex[start..start + 2], 16).map_err(|_| invalid());
+
+    Ok(tiny_skia::Color::from_rgba8(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        255,
+    ))
+}
+
+/// Checks every city visited by `tours` and `style` has coordinates
+fn validate<W>(cities: &[(f64, f64)], tours: &[&Tour<W>], style: &Style) -> Result<(), Error> {
+    for &city in tours.iter().flat_map(|tour| tour.route.iter()) {
+        validate_city(city, cities.len())?;
+    }
+
+    if let Some(depot) = style.depot {
+        validate_city(depot, cities.len())?;
+    }
+
+    Ok(())
+}
+
+fn draw_tours<W>(
+    cities: &[(f64, f64)],
+    tours: &[Tour<W>],
+    style: &Style,
+) -> Result<Drawing, Error> {
+    validate(cities, &tours.iter().collect::<Vec<_>>(), style)?;
+
+    let projection = Projection::new(cities, style);
+    let mut drawing = Drawing::new(style);
+
+    for (index, tour) in tours.iter().enumerate() {
+        for edge in tour.edges() {
+            drawing.add_edge(&projection, cities, edge, style.colour(index), style, false);
+        }
+    }
+
+    drawing.add_cities(&projection, cities, style);
+
+    Ok(drawing)
+}
+
+fn draw_diff<W, V>(
+    cities: &[(f64, f64)],
+    tour: &Tour<W>,
+    other: &Tour<V>,
+    style: &Style,
+) -> Result<Drawing, Error> {
+    validate(cities, &[tour], style)?;
+    validate(cities, &[other], style)?;
+
+    let projection = Projection::new(cities, style);
+    let mut drawing = Drawing::new(style);
+    let normalise = |(from, to): (usize, usize)| (from.min(to), from.max(to));
+
+    // ordered sets keep the edges, and so the image, the same on every run
+    let edges: BTreeSet<(usize, usize)> = tour.edges().map(normalise).collect();
+    let other_edges: BTreeSet<(usize, usize)> = other.edges().map(normalise).collect();
+
+    for &edge in edges.intersection(&other_edges) {
+        drawing.add_edge(&projection, cities, edge, "#bbbbbb", style, false);
+    }
+
+    for &edge in edges.difference(&other_edges) {
+        drawing.add_edge(&projection, cities, edge, style.colour(0), style, false);
+    }
+
+    for &edge in other_edges.difference(&edges) {
+        drawing.add_edge(&projection, cities, edge, style.colour(1), style, true);
+    }
+
+    drawing.add_cities(&projection, cities, style);
+
+    Ok(drawing)
+}
+
+/// Returns an SVG image of `cities` and `tours`
+///
+///# Parameters and Return Type
+///
+/// `cities` is an array slice, containing `(x,y)` tuple coordinates for each city.
+///
+/// `tours` is an array slice of `travelling_salesman::Tour` structs, such as the route of each
+/// vehicle, drawn in the colours of `style` in order.
+///
+/// `style` is a `travelling_salesman::render::Style`, specifying how the image looks.
+///
+/// Returns the SVG image as a `String`, or `travelling_salesman::Error::InvalidCity` if a tour
+/// or the depot is not one of the cities.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::render::Style;
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let tour = Tour {
+///    distance: 12.0,
+///    route: vec![0, 1, 2, 0],
+///    closed: true,
+///  };
+///
+///  let style = Style {
+///    labels: true,
+///    ..Style::default()
+///  };
+///
+///  let svg =
+///    travelling_salesman::render::to_svg(&[(0.0, 0.0), (3.0, 4.0), (3.0, 0.0)], &[tour], &style)
+///      .unwrap();
+///
+///  assert_eq!(svg.matches("<line").count(), 3);
+///  assert_eq!(svg.matches("<circle").count(), 3);
+///  assert_eq!(svg.matches("<text").count(), 3);
+///
+///  let style = Style {
+///    background: "\"/><script>".to_string(),
+///    ..Style::default()
+///  };
+///
+///  let svg = travelling_salesman::render::to_svg(&[(0.0, 0.0)], &[] as &[Tour], &style).unwrap();
+///
+///  assert!(svg.contains("fill=\"&quot;/>&lt;script>\""));
+///}
+///```
+pub fn to_svg<W>(cities: &[(f64, f64)], tours: &[Tour<W>], style: &Style) -> Result<String, Error> {
+    Ok(draw_tours(cities, tours, style)?.svg())
+}
+
+/// Returns an SVG image overlaying `tour` and `other`, to show where they differ
+///
+/// Edges travelled by both tours are grey. Edges only travelled by `tour` are drawn solid in the
+/// first colour of `style`, and edges only travelled by `other` are dashed in the second colour.
+///
+/// Returns `travelling_salesman::Error::InvalidCity` if either tour or the depot is not one of the
+/// cities.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::render::Style;
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let cities = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+///
+///  let tour = Tour {
+///    distance: 4.0,
+///    route: vec![0, 1, 2, 3, 0],
+///    closed: true,
+///  };
+///  let other = Tour {
+///    distance: 4.8,
+///    route: vec![0, 2, 1, 3, 0],
+///    closed: true,
+///  };
+///
+///  let svg = travelling_salesman::render::diff_to_svg(&cities, &tour, &other, &Style::default())
+///    .unwrap();
+///
+///  assert_eq!(svg.matches("stroke-dasharray").count(), 2);
+///  assert_eq!(
+///    svg,
+///    travelling_salesman::render::diff_to_svg(&cities, &tour, &other, &Style::default()).unwrap()
+///  );
+///}
+///```
+pub fn diff_to_svg<W, V>(
+    cities: &[(f64, f64)],
+    tour: &Tour<W>,
+    other: &Tour<V>,
+    style: &Style,
+) -> Result<String, Error> {
+    Ok(draw_diff(cities, tour, other, style)?.svg())
+}
+
+/// Returns a PNG image of `cities` and `tours`, drawn as by `to_svg` but without labels
+///
+/// Returns `travelling_salesman::Error::InvalidCity` if a tour or the depot is not one of the
+/// cities, or `travelling_salesman::Error::InvalidOptions` if the image size or a colour is not
+/// valid.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::render::Style;
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let tour = Tour {
+///    distance: 12.0,
+///    route: vec![0, 1, 2, 0],
+///    closed: true,
+///  };
+///
+///  let png =
+///    travelling_salesman::render::to_png(&[(0.0, 0.0), (3.0, 4.0), (3.0, 0.0)], &[tour], &Style::default())
+///      .unwrap();
+///
+///  assert_eq!(&png[1..4], b"PNG");
+///}
+///```
+#[cfg(feature = "png")]
+pub fn to_png<W>(
+    cities: &[(f64, f64)],
+    tours: &[Tour<W>],
+    style: &Style,
+) -> Result<Vec<u8>, Error> {
+    draw_tours(cities, tours, style)?.png()
+}
+
+/// Returns a PNG image overlaying `tour` and `other`, drawn as by `diff_to_svg` but without labels
+///
+/// Returns `travelling_salesman::Error::InvalidCity` if either tour or the depot is not one of the
+/// cities, or `travelling_salesman::Error::InvalidOptions` if the image size or a colour is not
+/// valid.
+#[cfg(feature = "png")]
+pub fn diff_to_png<W, V>(
+    cities: &[(f64, f64)],
+    tour: &Tour<W>,
+    other: &Tour<V>,
+    style: &Style,
+) -> Result<Vec<u8>, Error> {
+    draw_diff(cities, tour, other, style)?.png()
+}
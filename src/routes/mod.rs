@@ -0,0 +1,283 @@
+//! Local search over several routes, shared by the multiple-route solvers
+//!
+//! Each route starts and finishes at its own depot. Cities are moved between routes (relocate,
+//! exchange and 2-opt*), and each route is solved as a Travelling Salesman Problem using this
+//! crate's solvers.
+//!
+use rand::Rng;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::{DistanceOracle, Subset};
+use super::search::Budget;
+use super::{brute_force, simulated_annealing, Options};
+
+/// The largest route, excluding the depot, which is solved exactly using brute force
+const BRUTE_FORCE_CITIES: usize = 7;
+
+/// What the routes are judged on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Balance {
+    /// the total distance of every route
+    Sum,
+    /// the distance of the longest route, then the total distance
+    Max,
+}
+
+/// The routes of every vehicle, each listing the cities visited between leaving and returning to
+/// its depot
+pub struct Fleet<'a, D: DistanceOracle + ?Sized> {
+    pub distances: &'a D,
+    pub demands: &'a [u64],
+    pub capacity: u64,
+    pub balance: Balance,
+    pub depots: Vec<usize>,
+    pub routes: Vec<Vec<usize>>,
+    costs: Vec<f64>,
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Fleet<'a, D> {
+    /// Returns a fleet following `routes`, where route `index` starts and finishes at
+    /// `depots[index]`
+    pub fn new(
+        distances: &'a D,
+        demands: &'a [u64],
+        capacity: u64,
+        balance: Balance,
+        depots: Vec<usize>,
+        routes: Vec<Vec<usize>>,
+    ) -> Fleet<'a, D> {
+        let mut fleet = Fleet {
+            distances,
+            demands,
+            capacity,
+            balance,
+            depots,
+            routes,
+            costs: vec![],
+        };
+
+        fleet.costs = (0..fleet.routes.len())
+            .map(|index| fleet.cost(index, &fleet.routes[index]))
+            .collect();
+
+        fleet
+    }
+
+    pub fn distance(&self, from: usize, to: usize) -> f64 {
+        self.distances.distance(from, to).to_f64()
+    }
+
+    pub fn load(&self, route: &[usize]) -> u64 {
+        route.iter().map(|&city| self.demands[city]).sum()
+    }
+
+    /// Returns the distance of `route` if it were followed by vehicle `index`
+    pub fn cost(&self, index: usize, route: &[usize]) -> f64 {
+        let depot = self.depots[index];
+        let mut cost = 0.0;
+        let mut previous = depot;
+
+        for &city in route {
+            cost += self.distance(previous, city);
+            previous = city;
+        }
+
+        cost + self.distance(previous, depot)
+    }
+
+    pub fn closed_route(&self, index: usize) -> Vec<usize> {
+        let depot = self.depots[index];
+        let mut closed_route = vec![depot];
+        closed_route.extend_from_slice(&self.routes[index]);
+        closed_route.push(depot);
+        closed_route
+    }
+
+    /// Returns whether replacing routes `a` and `b` with `route_a` and `route_b` is an
+    /// improvement
+    fn improves(&self, a: usize, route_a: &[usize], b: usize, route_b: &[usize]) -> bool {
+        let (old_a, old_b) = (self.costs[a], self.costs[b]);
+        let (new_a, new_b) = (self.cost(a, route_a), self.cost(b, route_b));
+        let shorter = new_a + new_b < old_a + old_b - 1e-9;
+
+        match self.balance {
+            Balance::Sum => shorter,
+            Balance::Max => {
+                let others = self
+                    .costs
+                    .iter()
+                    .enumerate()
+                    .filter(|&(index, _)| index != a && index != b)
+                    .fold(0.0, |longest: f64, (_, &cost)| longest.max(cost));
+
+                let old_longest = others.max(old_a).max(old_b);
+                let new_longest = others.max(new_a).max(new_b);
+
+                new_longest < old_longest - 1e-9 || (new_longest <= old_longest + 1e-9 && shorter)
+            }
+        }
+    }
+
+    fn replace(&mut self, a: usize, route_a: Vec<usize>, b: usize, route_b: Vec<usize>) {
+        self.costs[a] = self.cost(a, &route_a);
+        self.costs[b] = self.cost(b, &route_b);
+        self.routes[a] = route_a;
+        self.routes[b] = route_b;
+    }
+
+    /// Applies improving moves between routes until none remain, or the budget is spent
+    pub fn improve(&mut self, budget: &mut Budget) {
+        while budget.next() {
+            let improved =
+                self.relocate(budget) || self.exchange(budget) || self.two_opt_star(budget);
+
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    /// Moves a city to the cheapest position in another route
+    fn relocate(&mut self, budget: &mut Budget) -> bool {
+        for a in 0..self.routes.len() {
+            for i in 0..self.routes[a].len() {
+                if !budget.next() {
+                    return false;
+                }
+
+                let city = self.routes[a][i];
+                let mut route_a = self.routes[a].clone();
+                route_a.remove(i);
+
+                for b in (0..self.routes.len()).filter(|&b| b != a) {
+                    if self.load(&self.routes[b]) + self.demands[city] > self.capacity {
+                        continue;
+                    }
+
+                    for position in 0..=self.routes[b].len() {
+                        let mut route_b = self.routes[b].clone();
+                        route_b.insert(position, city);
+
+                        if self.improves(a, &route_a, b, &route_b) {
+                            self.replace(a, route_a, b, route_b);
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Swaps two cities in different routes
+    fn exchange(&mut self, budget: &mut Budget) -> bool {
+        for a in 0..self.routes.len() {
+            for b in (a + 1)..self.routes.len() {
+                if !budget.next() {
+                    return false;
+                }
+
+                let (load_a, load_b) = (self.load(&self.routes[a]), self.load(&self.routes[b]));
+
+                for i in 0..self.routes[a].len() {
+                    for j in 0..self.routes[b].len() {
+                        let (city_a, city_b) = (self.routes[a][i], self.routes[b][j]);
+
+                        if load_a - self.demands[city_a] + self.demands[city_b] > self.capacity
+                            || load_b - self.demands[city_b] + self.demands[city_a] > self.capacity
+                        {
+                            continue;
+                        }
+
+                        let mut route_a = self.routes[a].clone();
+                        let mut route_b = self.routes[b].clone();
+                        route_a[i] = city_b;
+                        route_b[j] = city_a;
+
+                        if self.improves(a, &route_a, b, &route_b) {
+                            self.replace(a, route_a, b, route_b);
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Swaps the ends of two routes
+    fn two_opt_star(&mut self, budget: &mut Budget) -> bool {
+        for a in 0..self.routes.len() {
+            for b in (a + 1)..self.routes.len() {
+                if !budget.next() {
+                    return false;
+                }
+
+                for i in 0..=self.routes[a].len() {
+                    for j in 0..=self.routes[b].len() {
+                        let (head_a, tail_a) = self.routes[a].split_at(i);
+                        let (head_b, tail_b) = self.routes[b].split_at(j);
+
+                        let route_a: Vec<usize> = head_a.iter().chain(tail_b).cloned().collect();
+                        let route_b: Vec<usize> = head_b.iter().chain(tail_a).cloned().collect();
+
+                        if self.load(&route_a) > self.capacity
+                            || self.load(&route_b) > self.capacity
+                        {
+                            continue;
+                        }
+
+                        if self.improves(a, &route_a, b, &route_b) {
+                            self.replace(a, route_a, b, route_b);
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Solves each route as a Travelling Salesman Problem starting from its depot, keeping the
+    /// result if it is shorter, with searches seeded and limited by `options`
+    pub fn optimise_routes(&mut self, runtime_per_route: Duration, options: &Options) {
+        let mut rng = options.rng();
+
+        for index in 0..self.routes.len() {
+            let options = Options {
+                start_city: Some(0),
+                seed: Some(rng.gen()),
+                iterations: options.iterations,
+                ..Options::default()
+            };
+
+            let cities = self.closed_route(index);
+            let cities = &cities[..cities.len() - 1];
+            let subset = Subset::new(self.distances, cities);
+
+            let tour = if cities.len() <= BRUTE_FORCE_CITIES + 1 {
+                brute_force::solve_with_options(&subset, &options)
+            } else {
+                simulated_annealing::solve_with_options(&subset, runtime_per_route, &options)
+            };
+
+            if let Ok(tour) = tour {
+                let route: Vec<usize> = tour.route[1..tour.route.len() - 1]
+                    .iter()
+                    .map(|&city| cities[city])
+                    .collect();
+
+                let cost = self.cost(index, &route);
+
+                if cost < self.costs[index] {
+                    self.costs[index] = cost;
+                    self.routes[index] = route;
+                }
+            }
+        }
+    }
+}
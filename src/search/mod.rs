@@ -0,0 +1,159 @@
+//! Metaheuristic searches which take their random choices from a seeded generator
+//!
+//! Each search is limited by a `Budget` of either time or iterations, so a seeded search limited
+//! by iterations finds the same route on every run.
+//!
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::time::Instant;
+use time::Duration;
+
+/// A problem the searches can optimise, by generating, ranking and tweaking candidate solutions
+pub trait Metaheuristics<T> {
+    /// Returns a copy of `candidate`
+    fn clone_candidate(&mut self, candidate: &T) -> T;
+
+    /// Returns a new random candidate
+    fn generate_candidate(&mut self) -> T;
+
+    /// Returns a score for `candidate`, where higher is better
+    fn rank_candidate(&mut self, candidate: &T) -> f64;
+
+    /// Returns a copy of `candidate` with a small random change
+    fn tweak_candidate(&mut self, candidate: &T) -> T;
+}
+
+/// How long a search may run, as a runtime or a number of iterations
+pub struct Budget {
+    runtime: Duration,
+    iterations: Option<u64>,
+    start_time: Instant,
+    spent: u64,
+}
+
+impl Budget {
+    /// Returns a budget of `iterations` if given, and of `runtime` otherwise
+    pub fn new(runtime: Duration, iterations: Option<u64>) -> Budget {
+        Budget {
+            runtime,
+            iterations,
+            start_time: Instant::now(),
+            spent: 0,
+        }
+    }
+
+    /// Returns whether another iteration fits in the budget, counting it if so
+    pub fn next(&mut self) -> bool {
+        let remaining = match self.iterations {
+            Some(iterations) => self.spent < iterations,
+            None => self.runtime > self.start_time.elapsed(),
+        };
+
+        if remaining {
+            self.spent += 1;
+        }
+
+        remaining
+    }
+
+    /// Returns the portion of the budget spent, from zero to one
+    pub fn portion_spent(&self) -> f64 {
+        let portion = match self.iterations {
+            Some(0) => 1.0,
+            Some(iterations) => self.spent as f64 / iterations as f64,
+            None if self.runtime <= Duration::ZERO => 1.0,
+            None => self.start_time.elapsed().as_secs_f64() / self.runtime.as_seconds_f64(),
+        };
+
+        portion.min(1.0)
+    }
+}
+
+/// Returns the best candidate found by repeatedly tweaking the best so far
+pub fn hill_climbing<T>(problem: &mut dyn Metaheuristics<T>, budget: &mut Budget) -> T {
+    let mut best_candidate = problem.generate_candidate();
+
+    while budget.next() {
+        let next_candidate = problem.tweak_candidate(&best_candidate);
+
+        if problem.rank_candidate(&next_candidate) > problem.rank_candidate(&best_candidate) {
+            best_candidate = next_candidate;
+        }
+    }
+
+    best_candidate
+}
+
+/// Returns the best candidate found by hill climbing, restarting from a new candidate with
+/// `probability` on each iteration
+pub fn random_restarts<T>(
+    problem: &mut dyn Metaheuristics<T>,
+    budget: &mut Budget,
+    probability: f64,
+    rng: &mut StdRng,
+) -> T {
+    let mut best_candidate = problem.generate_candidate();
+    let mut current_candidate = problem.clone_candidate(&best_candidate);
+
+    while budget.next() {
+        if probability > rng.gen_range(0.0..1.0) {
+            current_candidate = problem.generate_candidate();
+        } else {
+            let next_candidate = problem.tweak_candidate(&current_candidate);
+
+            if problem.rank_candidate(&next_candidate) > problem.rank_candidate(&current_candidate)
+            {
+                current_candidate = next_candidate;
+            }
+        }
+
+        if problem.rank_candidate(&current_candidate) > problem.rank_candidate(&best_candidate) {
+            best_candidate = problem.clone_candidate(&current_candidate);
+        }
+    }
+
+    best_candidate
+}
+
+/// Returns the best of the candidates generated
+pub fn random_search<T>(problem: &mut dyn Metaheuristics<T>, budget: &mut Budget) -> T {
+    let mut best_candidate = problem.generate_candidate();
+
+    while budget.next() {
+        let next_candidate = problem.generate_candidate();
+
+        if problem.rank_candidate(&next_candidate) > problem.rank_candidate(&best_candidate) {
+            best_candidate = next_candidate;
+        }
+    }
+
+    best_candidate
+}
+
+/// Returns the best candidate found by simulated annealing, which accepts a worse tweak with a
+/// probability falling from one to almost zero as the budget is spent
+pub fn simulated_annealing<T>(
+    problem: &mut dyn Metaheuristics<T>,
+    budget: &mut Budget,
+    rng: &mut StdRng,
+) -> T {
+    let mut best_candidate = problem.generate_candidate();
+    let mut annealing_candidate = problem.clone_candidate(&best_candidate);
+
+    while budget.next() {
+        let next_candidate = problem.tweak_candidate(&annealing_candidate);
+        let next_is_better =
+            problem.rank_candidate(&next_candidate) > problem.rank_candidate(&annealing_candidate);
+        let replacement_threshold = (-10.0 * budget.portion_spent().powi(3)).exp();
+
+        if next_is_better || rng.gen_range(0.0..1.0) < replacement_threshold {
+            annealing_candidate = next_candidate;
+        }
+
+        if problem.rank_candidate(&annealing_candidate) > problem.rank_candidate(&best_candidate) {
+            best_candidate = problem.clone_candidate(&annealing_candidate);
+        }
+    }
+
+    best_candidate
+}
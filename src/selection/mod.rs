@@ -0,0 +1,366 @@
+//! Local search over which cities to visit, shared by the prize collecting solvers
+//!
+//! A closed route from the depot is grown and shrunk by inserting and dropping cities, improved by
+//! replacing cities and reversing sections, then perturbed by dropping a few cities at random
+//! whenever no move improves it.
+//!
+use rand::Rng;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::orienteering::PrizeTour;
+use super::search::Budget;
+use super::{get_route_distance, validate_city, Error, Options, Tour};
+
+/// How a route is judged, given its length and collected prize
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Goal {
+    /// collect the most prize within a length budget, then travel the least
+    Budget(f64),
+    /// travel the least plus the prize of every city skipped
+    Penalties(f64),
+}
+
+impl Goal {
+    fn feasible(&self, length: f64) -> bool {
+        match *self {
+            Goal::Budget(budget) => length <= budget + 1e-9,
+            Goal::Penalties(_) => true,
+        }
+    }
+
+    /// Returns whether a route of `length` collecting `prize` is better than one of
+    /// `best_length` collecting `best_prize`
+    fn better(&self, (length, prize): (f64, f64), (best_length, best_prize): (f64, f64)) -> bool {
+        if !self.feasible(length) {
+            return false;
+        }
+
+        match *self {
+            Goal::Budget(_) => {
+                prize > best_prize + 1e-9
+                    || (prize >= best_prize - 1e-9 && length < best_length - 1e-9)
+            }
+            Goal::Penalties(total) => {
+                length + total - prize < best_length + total - best_prize - 1e-9
+            }
+        }
+    }
+}
+
+/// A closed route from the depot, listing the cities visited after leaving it
+#[derive(Clone)]
+struct Route {
+    cities: Vec<usize>,
+    length: f64,
+    prize: f64,
+}
+
+struct Search<'a, D: DistanceOracle + ?Sized> {
+    distances: &'a D,
+    prizes: &'a [f64],
+    depot: usize,
+    goal: Goal,
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Search<'a, D> {
+    fn distance(&self, from: usize, to: usize) -> f64 {
+        self.distances.distance(from, to).to_f64()
+    }
+
+    fn length(&self, cities: &[usize]) -> f64 {
+        let mut length = 0.0;
+        let mut previous = self.depot;
+
+        for &city in cities {
+            length += self.distance(previous, city);
+            previous = city;
+        }
+
+        length + self.distance(previous, self.depot)
+    }
+
+    /// Returns the cities before and after `position`, where the route wraps around the depot
+    fn neighbours(&self, cities: &[usize], position: usize) -> (usize, usize) {
+        let previous = if position == 0 {
+            self.depot
+        } else {
+            cities[position - 1]
+        };
+
+        (
+            previous,
+            cities.get(position).cloned().unwrap_or(self.depot),
+        )
+    }
+
+    /// Returns the cheapest position to insert `city` into `cities`, and the added length
+    fn cheapest_insertion(&self, cities: &[usize], city: usize) -> (usize, f64) {
+        (0..=cities.len())
+            .map(|position| {
+                let (previous, next) = self.neighbours(cities, position);
+
+                (
+                    position,
+                    self.distance(previous, city) + self.distance(city, next)
+                        - self.distance(previous, next),
+                )
+            })
+            .fold((0, f64::INFINITY), |best, candidate| {
+                if candidate.1 < best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            })
+    }
+
+    fn unvisited(&self, cities: &[usize]) -> Vec<usize> {
+        let mut visited = vec![false; self.distances.size()];
+        visited[self.depot] = true;
+
+        for &city in cities {
+            visited[city] = true;
+        }
+
+        (0..visited.len()).filter(|&city| !visited[city]).collect()
+    }
+
+    fn score(&self, route: &Route) -> (f64, f64) {
+        (route.length, route.prize)
+    }
+
+    /// Inserts an unvisited city at its cheapest position
+    fn insert(&self, route: &mut Route) -> bool {
+        for city in self.unvisited(&route.cities) {
+            let (position, added) = self.cheapest_insertion(&route.cities, city);
+            let candidate = (route.length + added, route.prize + self.prizes[city]);
+
+            if self.goal.better(candidate, self.score(route)) {
+                route.cities.insert(position, city);
+                route.length = candidate.0;
+                route.prize = candidate.1;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Drops a visited city
+    fn drop(&self, route: &mut Route) -> bool {
+        for position in 0..route.cities.len() {
+            let city = route.cities[position];
+            let previous = self.neighbours(&route.cities, position).0;
+            let next = self.neighbours(&route.cities, position + 1).1;
+            let removed = self.distance(previous, city) + self.distance(city, next)
+                - self.distance(previous, next);
+            let candidate = (route.length - removed, route.prize - self.prizes[city]);
+
+            if self.goal.better(candidate, self.score(route)) {
+                route.cities.remove(position);
+                route.length = candidate.0;
+                route.prize = candidate.1;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Replaces a visited city with an unvisited city at its cheapest position
+    fn replace(&self, route: &mut Route, budget: &mut Budget) -> bool {
+        let unvisited = self.unvisited(&route.cities);
+
+        for position in 0..route.cities.len() {
+            if !budget.next() {
+                return false;
+            }
+
+            let city = route.cities[position];
+            let mut cities = route.cities.clone();
+            cities.remove(position);
+            let length = self.length(&cities);
+
+            for &replacement in &unvisited {
+                let (insert_at, added) = self.cheapest_insertion(&cities, replacement);
+                let candidate = (
+                    length + added,
+                    route.prize - self.prizes[city] + self.prizes[replacement],
+                );
+
+                if self.goal.better(candidate, self.score(route)) {
+                    cities.insert(insert_at, replacement);
+                    route.cities = cities;
+                    route.length = candidate.0;
+                    route.prize = candidate.1;
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Reverses a section of the route
+    fn reverse(&self, route: &mut Route, budget: &mut Budget) -> bool {
+        for start in 0..route.cities.len() {
+            if !budget.next() {
+                return false;
+            }
+
+            for end in (start + 1)..route.cities.len() {
+                let mut cities = route.cities.clone();
+                cities[start..=end].reverse();
+                let length = self.length(&cities);
+
+                if length < route.length - 1e-9 {
+                    route.cities = cities;
+                    route.length = length;
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Applies improving moves until none remain, or the budget is spent
+    fn improve(&self, route: &mut Route, budget: &mut Budget) {
+        while budget.next() {
+            let improved = self.insert(route)
+                || self.drop(route)
+                || self.reverse(route, budget)
+                || self.replace(route, budget);
+
+            if !improved {
+                break;
+            }
+        }
+    }
+
+    /// Drops up to half of the visited cities at random, then inserts as many unvisited cities
+    /// at random, where they fit
+    fn perturb<R: Rng>(&self, rng: &mut R, route: &Route) -> Route {
+        let mut cities = route.cities.clone();
+        let count = rng.gen_range(1..=cities.len() / 2 + 1);
+
+        for _ in 0..count {
+            if cities.is_empty() {
+                break;
+            }
+
+            let position = rng.gen_range(0..cities.len());
+            cities.remove(position);
+        }
+
+        let mut length = self.length(&cities);
+        let mut unvisited = self.unvisited(&cities);
+
+        for _ in 0..count {
+            if unvisited.is_empty() {
+                break;
+            }
+
+            let city = unvisited.swap_remove(rng.gen_range(0..unvisited.len()));
+            let (position, added) = self.cheapest_insertion(&cities, city);
+
+            if self.goal.feasible(length + added) {
+                cities.insert(position, city);
+                length += added;
+            }
+        }
+
+        Route {
+            length,
+            prize: cities.iter().map(|&city| self.prizes[city]).sum(),
+            cities,
+        }
+    }
+}
+
+/// Returns the cities visited after leaving `depot` by the best route found within `runtime`, or
+/// the iterations of `options`, seeded by `options`
+pub fn search<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    prizes: &[f64],
+    depot: usize,
+    goal: Goal,
+    runtime: Duration,
+    options: &Options,
+) -> Vec<usize> {
+    let search = Search {
+        distances,
+        prizes,
+        depot,
+        goal,
+    };
+
+    let mut rng = options.rng();
+    let mut budget = options.budget(runtime);
+
+    let mut best = Route {
+        cities: vec![],
+        length: 0.0,
+        prize: 0.0,
+    };
+
+    search.improve(&mut best, &mut budget);
+
+    while budget.next() {
+        let mut route = search.perturb(&mut rng, &best);
+        search.improve(&mut route, &mut budget);
+
+        if goal.better(search.score(&route), search.score(&best)) {
+            best = route;
+        }
+    }
+
+    best.cities
+}
+
+/// Checks there is one prize for each city, and the depot is in the problem
+pub fn validate(size: usize, prizes: &[f64], depot: usize) -> Result<(), Error> {
+    if prizes.len() != size {
+        return Err(Error::InvalidOptions(format!(
+            "expected {} prizes, but got {}",
+            size,
+            prizes.len()
+        )));
+    }
+
+    validate_city(depot, size)
+}
+
+/// Returns the closed tour from `depot` through `cities`, with the prize collected and the cities
+/// skipped
+pub fn prize_tour<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    prizes: &[f64],
+    depot: usize,
+    cities: Vec<usize>,
+) -> PrizeTour<D::Weight> {
+    let mut visited = vec![false; distances.size()];
+    visited[depot] = true;
+
+    for &city in &cities {
+        visited[city] = true;
+    }
+
+    let prize = cities.iter().map(|&city| prizes[city]).sum();
+
+    let mut route = vec![depot];
+    route.extend(cities);
+    route.push(depot);
+
+    PrizeTour {
+        tour: Tour {
+            distance: get_route_distance(distances, &route),
+            route,
+            closed: true,
+        },
+        prize,
+        skipped: (0..visited.len()).filter(|&city| !visited[city]).collect(),
+    }
+}
@@ -0,0 +1,606 @@
+//! Find an approximate solution to the Time-Dependent Travelling Salesman Problem
+//!
+//! The time taken to travel between two cities depends on when the salesman leaves, such as
+//! driving through a city centre at rush hour. Travel times are given either as a piecewise-linear
+//! profile for each pair of cities, or as a stack of matrices for consecutive time slices, and are
+//! interpolated linearly between the departure times given.
+//!
+//! Interpolation keeps the first-in-first-out property: leaving later never means arriving
+//! earlier. If a profile would arrive earlier by leaving later, the salesman is assumed to wait
+//! and leave later instead.
+//!
+//! Routes are evaluated by simulating the arrival time at each city from the start time, and
+//! improved by relocating, swapping and reversing cities to minimise the total duration.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::time_dependent::TimeSlices;
+//!
+//!fn main() {
+//!  let off_peak = vec![
+//!    vec![0.0, 10.0, 15.0, 20.0],
+//!    vec![10.0, 0.0, 35.0, 25.0],
+//!    vec![15.0, 35.0, 0.0, 30.0],
+//!    vec![20.0, 25.0, 30.0, 0.0],
+//!  ];
+//!  let rush_hour = vec![
+//!    vec![0.0, 40.0, 15.0, 20.0],
+//!    vec![40.0, 0.0, 35.0, 25.0],
+//!    vec![15.0, 35.0, 0.0, 30.0],
+//!    vec![20.0, 25.0, 30.0, 0.0],
+//!  ];
+//!
+//!  let travel_times = TimeSlices::new(vec![off_peak.clone(), rush_hour, off_peak], 60.0).unwrap();
+//!
+//!  let timed_tour = travelling_salesman::time_dependent::solve(
+//!    &travel_times,
+//!    0,
+//!    30.0,
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  println!(
+//!    "Route: {:?}, arrivals: {:?}, duration: {}",
+//!    timed_tour.tour.route, timed_tour.arrivals, timed_tour.makespan,
+//!  );
+//!}
+//!```
+//!
+use time::Duration;
+
+use super::moves::{random_move, random_relocation};
+use super::time_windows::TimedTour;
+use super::{validate_city, Error, Options, Tour};
+
+/// Answers travel time queries between cities, where the time depends on the departure
+pub trait TravelTimes {
+    /// Returns the number of cities
+    fn size(&self) -> usize;
+
+    /// Returns the time taken to travel from city `from` to city `to`, leaving at `departure`
+    fn travel_time(&self, from: usize, to: usize, departure: f64) -> f64;
+}
+
+/// Travel times given by a piecewise-linear profile for each pair of cities
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_dependent::{Profiles, TravelTimes};
+///
+///fn main() {
+///  // the trip from city 0 to city 1 takes 10 until time 60, rising to 30 by time 120
+///
+///  let profiles = Profiles::new(vec![
+///    vec![vec![], vec![(60.0, 10.0), (120.0, 30.0)]],
+///    vec![vec![(0.0, 10.0)], vec![]],
+///  ])
+///  .unwrap();
+///
+///  assert_eq!(profiles.travel_time(0, 1, 0.0), 10.0);
+///  assert_eq!(profiles.travel_time(0, 1, 90.0), 20.0);
+///  assert_eq!(profiles.travel_time(0, 1, 200.0), 30.0);
+///  assert_eq!(profiles.travel_time(1, 0, 90.0), 10.0);
+///}
+///```
+pub struct Profiles {
+    profiles: Vec<Vec<Vec<(f64, f64)>>>,
+}
+
+impl Profiles {
+    /// Returns the travel times given by `profiles`
+    ///
+    /// `profiles[from][to]` is a `Vec` of `(departure, travel_time)` tuples in ascending order of
+    /// departure. Travel times are interpolated linearly between departures, and held constant
+    /// before the first and after the last. The profile from a city to itself may be empty.
+    ///
+    /// Returns `travelling_salesman::Error::InvalidOptions` if the profiles are not square, a
+    /// profile between two cities is empty, or its departures are out of order.
+    pub fn new(profiles: Vec<Vec<Vec<(f64, f64)>>>) -> Result<Profiles, Error> {
+        let size = profiles.len();
+
+        for (from, row) in profiles.iter().enumerate() {
+            if row.len() != size {
+                return Err(Error::InvalidOptions(format!(
+                    "expected {} profiles from city {}, but got {}",
+                    size,
+                    from,
+                    row.len()
+                )));
+            }
+
+            for (to, profile) in row.iter().enumerate() {
+                if profile.is_empty() && from != to {
+                    return Err(Error::InvalidOptions(format!(
+                        "the profile from city {} to city {} is empty",
+                        from, to
+                    )));
+                }
+
+                if profile.windows(2).any(|pair| pair[0].0 > pair[1].0) {
+                    return Err(Error::InvalidOptions(format!(
+                        "the departures from city {} to city {} are out of order",
+                        from, to
+                    )));
+                }
+            }
+        }
+
+        Ok(Profiles { profiles })
+    }
+}
+
+impl TravelTimes for Profiles {
+    fn size(&self) -> usize {
+        self.profiles.len()
+    }
+
+    fn travel_time(&self, from: usize, to: usize, departure: f64) -> f64 {
+        let profile = &self.profiles[from][to];
+
+        fifo_travel_time(profile.len(), |index| profile[index], departure)
+    }
+}
+
+/// Travel times given by a matrix for each consecutive time slice
+///
+/// Matrix `k` applies when leaving at time `k * slice_length`, and travel times are interpolated
+/// linearly between slices. The first matrix applies before the first slice, and the last matrix
+/// after the last slice.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_dependent::{TimeSlices, TravelTimes};
+///
+///fn main() {
+///  let time_slices = TimeSlices::new(
+///    vec![
+///      vec![vec![0.0, 10.0], vec![10.0, 0.0]],
+///      vec![vec![0.0, 30.0], vec![30.0, 0.0]],
+///    ],
+///    60.0,
+///  )
+///  .unwrap();
+///
+///  assert_eq!(time_slices.travel_time(0, 1, 30.0), 20.0);
+///  assert_eq!(time_slices.travel_time(1, 0, 90.0), 30.0);
+///}
+///```
+pub struct TimeSlices {
+    matrices: Vec<Vec<Vec<f64>>>,
+    slice_length: f64,
+}
+
+impl TimeSlices {
+    /// Returns the travel times given by `matrices`, one for each slice of `slice_length`
+    ///
+    /// Returns `travelling_salesman::Error::InvalidOptions` if there are no matrices, they are
+    /// not all square and of the same size, or the slice length is not positive.
+    pub fn new(matrices: Vec<Vec<Vec<f64>>>, slice_length: f64) -> Result<TimeSlices, Error> {
+        if matrices.is_empty() {
+            return Err(Error::InvalidOptions(
+                "there must be at least one time slice".to_string(),
+            ));
+        }
+
+        if slice_length <= 0.0 || slice_length.is_nan() {
+            return Err(Error::InvalidOptions(format!(
+                "the slice length {} must be positive",
+                slice_length
+            )));
+        }
+
+        let size = matrices[0].len();
+
+        for (slice, matrix) in matrices.iter().enumerate() {
+            if matrix.len() != size || matrix.iter().any(|row| row.len() != size) {
+                return Err(Error::InvalidOptions(format!(
+                    "the matrix for time slice {} is not {} by {}",
+                    slice, size, size
+                )));
+            }
+        }
+
+        Ok(TimeSlices {
+            matrices,
+            slice_length,
+        })
+    }
+}
+
+impl TravelTimes for TimeSlices {
+    fn size(&self) -> usize {
+        self.matrices[0].len()
+    }
+
+    fn travel_time(&self, from: usize, to: usize, departure: f64) -> f64 {
+        fifo_travel_time(
+            self.matrices.len(),
+            |slice| {
+                (
+                    slice as f64 * self.slice_length,
+                    self.matrices[slice][from][to],
+                )
+            },
+            departure,
+        )
+    }
+}
+
+/// Returns the travel time at `departure`, interpolated between `count` `(departure,
+/// travel_time)` points in ascending order of departure
+///
+/// If leaving at an earlier point would arrive later than leaving at `departure`, the salesman
+/// waits until that point instead, so leaving later never arrives earlier.
+fn fifo_travel_time<P: Fn(usize) -> (f64, f64)>(count: usize, point: P, departure: f64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+
+    let next = (0..count)
+        .position(|index| point(index).0 > departure)
+        .unwrap_or(count);
+
+    let travel_time = if next == 0 {
+        point(0).1
+    } else if next == count {
+        point(count - 1).1
+    } else {
+        let (start, start_time) = point(next - 1);
+        let (end, end_time) = point(next);
+
+        start_time + (end_time - start_time) * (departure - start) / (end - start)
+    };
+
+    // arriving no earlier than leaving at any later point would
+
+    let arrival = (next..count)
+        .map(|index| {
+            let (later, later_time) = point(index);
+            later + later_time
+        })
+        .fold(departure + travel_time, f64::min);
+
+    arrival - departure
+}
+
+/// Returns the time taken to travel along `route`, leaving the first city at `start_time`
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_dependent::TimeSlices;
+///
+///fn main() {
+///  let time_slices = TimeSlices::new(
+///    vec![
+///      vec![vec![0.0, 10.0], vec![10.0, 0.0]],
+///      vec![vec![0.0, 30.0], vec![30.0, 0.0]],
+///    ],
+///    60.0,
+///  )
+///  .unwrap();
+///
+///  // leave at 0 and arrive at 10, then leave at 10 and take 10 + 20 * 10 / 60
+///
+///  let duration = travelling_salesman::time_dependent::get_route_duration(&time_slices, &[0, 1, 0], 0.0);
+///
+///  assert!((duration - 70.0 / 3.0).abs() < 1e-9);
+///}
+///```
+pub fn get_route_duration<T: TravelTimes + ?Sized>(
+    travel_times: &T,
+    route: &[usize],
+    start_time: f64,
+) -> f64 {
+    let mut time = start_time;
+
+    for leg in route.windows(2) {
+        time += travel_times.travel_time(leg[0], leg[1], time);
+    }
+
+    time - start_time
+}
+
+/// Returns an approximate solution to the Time-Dependent Travelling Salesman Problem
+///
+///# Parameters and Return Type
+///
+/// `travel_times` is a `travelling_salesman::time_dependent::TravelTimes`, such as
+/// `travelling_salesman::time_dependent::Profiles` or
+/// `travelling_salesman::time_dependent::TimeSlices`.
+///
+/// `depot` is the city the route starts and finishes at.
+///
+/// `start_time` is the time the route leaves the depot.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::time_windows::TimedTour` struct, with the time of arrival at
+/// each city, where the tour distance and the makespan are both the total duration of the route.
+/// Returns `travelling_salesman::Error::InvalidCity` if the depot is not a city.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_dependent::TimeSlices;
+///
+///fn main() {
+///  // the edge between cities 0 and 1 is only quick before time 60, and the edge between cities 0
+///  // and 3 is only quick after it
+///
+///  let early = vec![
+///    vec![0.0, 10.0, 30.0, 50.0],
+///    vec![10.0, 0.0, 10.0, 20.0],
+///    vec![30.0, 10.0, 0.0, 10.0],
+///    vec![50.0, 20.0, 10.0, 0.0],
+///  ];
+///  let late = vec![
+///    vec![0.0, 50.0, 30.0, 10.0],
+///    vec![50.0, 0.0, 10.0, 20.0],
+///    vec![30.0, 10.0, 0.0, 10.0],
+///    vec![10.0, 20.0, 10.0, 0.0],
+///  ];
+///
+///  let travel_times = TimeSlices::new(vec![early, late], 60.0).unwrap();
+///
+///  let timed_tour = travelling_salesman::time_dependent::solve(
+///    &travel_times,
+///    0,
+///    0.0,
+///    time::Duration::milliseconds(100),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(timed_tour.tour.route, vec![0, 1, 2, 3, 0]);
+///}
+///```
+pub fn solve<T: TravelTimes + ?Sized>(
+    travel_times: &T,
+    depot: usize,
+    start_time: f64,
+    runtime: Duration,
+) -> Result<TimedTour, Error> {
+    solve_with_options(
+        travel_times,
+        depot,
+        start_time,
+        runtime,
+        &Options::default(),
+    )
+}
+
+/// Returns an approximate solution to the Time-Dependent Travelling Salesman Problem, seeded and
+/// limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `travel_times` is a `travelling_salesman::time_dependent::TravelTimes`, such as
+/// `travelling_salesman::time_dependent::Profiles` or
+/// `travelling_salesman::time_dependent::TimeSlices`.
+///
+/// `depot` is the city the route starts and finishes at.
+///
+/// `start_time` is the time the route leaves the depot.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::time_windows::TimedTour` struct, with the time of arrival at
+/// each city, where the tour distance and the makespan are both the total duration of the route.
+/// Returns `travelling_salesman::Error::InvalidCity` if the depot is not a city.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_dependent::TimeSlices;
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(10, 100.0, 7);
+///  let early = travelling_salesman::get_distance_matrix(&cities);
+///  let late = travelling_salesman::generators::asymmetric(10, 100.0, 7);
+///
+///  let travel_times = TimeSlices::new(vec![early, late], 200.0).unwrap();
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(10_000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::time_dependent::solve_with_options(
+///      &travel_times,
+///      0,
+///      0.0,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///    .tour
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<T: TravelTimes + ?Sized>(
+    travel_times: &T,
+    depot: usize,
+    start_time: f64,
+    runtime: Duration,
+    options: &Options,
+) -> Result<TimedTour, Error> {
+    options.validate_search_only()?;
+
+    let size = travel_times.size();
+
+    validate_city(depot, size)?;
+
+    let schedule = Schedule {
+        travel_times,
+        depot,
+        start_time,
+    };
+
+    let mut current = schedule.evaluate(schedule.nearest_route());
+    let mut best = current.clone();
+    let mut rng = options.rng();
+    let mut budget = options.budget(runtime);
+    let mut stale_iterations = 0;
+
+    while current.route.len() >= 2 && budget.next() {
+        let (changed_from, next_route) = random_move(&mut rng, &current.route);
+        let next = schedule.evaluate_from(&current, next_route, changed_from);
+
+        if next.duration <= current.duration {
+            current = next;
+            stale_iterations = 0;
+        } else {
+            stale_iterations += 1;
+        }
+
+        if current.duration < best.duration {
+            best = current.clone();
+        }
+
+        // escape local optima by perturbing the best route found so far
+
+        if stale_iterations > 50 * current.route.len() {
+            let mut route = best.route.clone();
+
+            for _ in 0..3 {
+                route = random_relocation(&mut rng, &route).1;
+            }
+
+            current = schedule.evaluate(route);
+            stale_iterations = 0;
+        }
+    }
+
+    Ok(schedule.timed_tour(&best.route))
+}
+
+/// The problem data needed to schedule a route
+struct Schedule<'a, T: TravelTimes + ?Sized> {
+    travel_times: &'a T,
+    depot: usize,
+    start_time: f64,
+}
+
+/// A route of every city except the depot, with its arrival times cached for incremental
+/// evaluation
+#[derive(Clone)]
+struct State {
+    route: Vec<usize>,
+    /// the time of arrival at each city of the route
+    arrivals: Vec<f64>,
+    duration: f64,
+}
+
+impl<'a, T: TravelTimes + ?Sized> Schedule<'a, T> {
+    /// Returns a route visiting the city which can be reached soonest each time
+    fn nearest_route(&self) -> Vec<usize> {
+        let mut unvisited: Vec<usize> = (0..self.travel_times.size())
+            .filter(|&city| city != self.depot)
+            .collect();
+        let mut route = vec![];
+        let mut city = self.depot;
+        let mut time = self.start_time;
+
+        while !unvisited.is_empty() {
+            let (index, travel_time) = unvisited
+                .iter()
+                .map(|&next| self.travel_times.travel_time(city, next, time))
+                .enumerate()
+                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                .expect("there is an unvisited city");
+
+            city = unvisited.swap_remove(index);
+            time += travel_time;
+            route.push(city);
+        }
+
+        route
+    }
+
+    fn evaluate(&self, route: Vec<usize>) -> State {
+        let empty = State {
+            route: vec![],
+            arrivals: vec![],
+            duration: 0.0,
+        };
+
+        self.evaluate_from(&empty, route, 0)
+    }
+
+    /// Evaluates `route`, which matches `previous.route` before position `changed_from`
+    fn evaluate_from(&self, previous: &State, route: Vec<usize>, changed_from: usize) -> State {
+        let mut arrivals = previous.arrivals[..changed_from].to_vec();
+
+        let (mut time, mut city) = if changed_from == 0 {
+            (self.start_time, self.depot)
+        } else {
+            (arrivals[changed_from - 1], route[changed_from - 1])
+        };
+
+        for &next_city in &route[changed_from..] {
+            time += self.travel_times.travel_time(city, next_city, time);
+            city = next_city;
+            arrivals.push(time);
+        }
+
+        time += self.travel_times.travel_time(city, self.depot, time);
+
+        State {
+            route,
+            arrivals,
+            duration: time - self.start_time,
+        }
+    }
+
+    fn timed_tour(&self, route: &[usize]) -> TimedTour {
+        let state = self.evaluate(route.to_vec());
+
+        let mut full_route = vec![self.depot];
+        full_route.extend_from_slice(route);
+        full_route.push(self.depot);
+
+        let mut arrivals = vec![self.start_time];
+        arrivals.extend_from_slice(&state.arrivals);
+        arrivals.push(self.start_time + state.duration);
+
+        TimedTour {
+            tour: Tour {
+                distance: state.duration,
+                route: full_route,
+                closed: true,
+            },
+            service_starts: arrivals.clone(),
+            arrivals,
+            makespan: state.duration,
+        }
+    }
+}
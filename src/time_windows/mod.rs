@@ -0,0 +1,490 @@
+//! Find an approximate solution to the Travelling Salesman Problem with Time Windows
+//!
+//! Each city must be visited within its `[earliest, latest]` time window, after which its service
+//! takes a fixed duration. Arriving before a window opens is allowed, but the salesman must wait
+//! until it does. The route starts from the depot when its window opens, and must return before it
+//! closes.
+//!
+//! Routes are improved by relocating, swapping and reversing cities. Each move is evaluated
+//! incrementally from the first city it changes, reusing the schedule up to that point, and once a
+//! feasible route has been found, moves are abandoned as soon as they miss a window.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!use travelling_salesman::time_windows::Objective;
+//!
+//!fn main() {
+//!  let travel_times = vec![
+//!    vec![0.0, 10.0, 15.0, 20.0],
+//!    vec![10.0, 0.0, 35.0, 25.0],
+//!    vec![15.0, 35.0, 0.0, 30.0],
+//!    vec![20.0, 25.0, 30.0, 0.0],
+//!  ];
+//!
+//!  let timed_tour = travelling_salesman::time_windows::solve(
+//!    &travel_times,
+//!    &[0.0, 5.0, 5.0, 5.0],
+//!    &[(0.0, 200.0), (50.0, 60.0), (0.0, 20.0), (100.0, 120.0)],
+//!    0,
+//!    Objective::Makespan,
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  assert_eq!(timed_tour.tour.route, vec![0, 2, 1, 3, 0]);
+//!
+//!  println!(
+//!    "Route: {:?}, arrivals: {:?}, makespan: {}",
+//!    timed_tour.tour.route, timed_tour.arrivals, timed_tour.makespan,
+//!  );
+//!}
+//!```
+//!
+use time::Duration;
+
+use super::matrix::Weight;
+use super::moves::{random_move, random_relocation};
+use super::oracle::DistanceOracle;
+use super::{validate_city, Error, Options, Tour};
+
+/// The quantity minimised by `solve`
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Objective {
+    /// the time from leaving the depot until returning, including waiting and service
+    Makespan,
+    /// the total time spent travelling between cities
+    TravelTime,
+}
+
+/// Represents a tour with a time of arrival at each stop
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimedTour {
+    /// the tour, where the distance is the total travel time
+    pub tour: Tour,
+    /// the time of arrival at each city of the route, starting with the departure from the depot
+    pub arrivals: Vec<f64>,
+    /// the time service started at each city of the route, which is later than the arrival if the
+    /// salesman had to wait for the time window to open
+    pub service_starts: Vec<f64>,
+    /// the time from leaving the depot until returning
+    pub makespan: f64,
+}
+
+/// Returns an approximate solution to the Travelling Salesman Problem with Time Windows
+///
+///# Parameters and Return Type
+///
+/// `travel_times` is a `travelling_salesman::oracle::DistanceOracle`, containing the time taken
+/// to travel between each pair of cities.
+///
+/// `service_times` is an array slice, containing the time spent at each city.
+///
+/// `time_windows` is an array slice, containing an `(earliest, latest)` tuple for each city
+/// specifying when its service may start. The window of the depot specifies when the route may
+/// leave and when it must have returned.
+///
+/// `depot` is the city the route starts and finishes at.
+///
+/// `objective` is a `travelling_salesman::time_windows::Objective`, specifying what to minimise.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `travelling_salesman::time_windows::TimedTour` struct, representing the approximate
+/// solution found, or `travelling_salesman::Error::Infeasible` if some city can never be served
+/// within its window, or no feasible route was found within the runtime.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_windows::Objective;
+///use travelling_salesman::Error;
+///
+///fn main() {
+///  let result = travelling_salesman::time_windows::solve(
+///    &vec![vec![0.0, 30.0], vec![30.0, 0.0]],
+///    &[0.0, 5.0],
+///    &[(0.0, 100.0), (0.0, 20.0)],
+///    0,
+///    Objective::TravelTime,
+///    time::Duration::seconds(1),
+///  );
+///
+///  match result {
+///    Err(Error::Infeasible(reason)) => println!("No feasible tour: {}", reason),
+///    _ => panic!("city 1 cannot be reached before its window closes"),
+///  }
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    travel_times: &D,
+    service_times: &[f64],
+    time_windows: &[(f64, f64)],
+    depot: usize,
+    objective: Objective,
+    runtime: Duration,
+) -> Result<TimedTour, Error> {
+    solve_with_options(
+        travel_times,
+        service_times,
+        time_windows,
+        depot,
+        objective,
+        runtime,
+        &Options::default(),
+    )
+}
+
+/// Returns an approximate solution to the Travelling Salesman Problem with Time Windows, seeded
+/// and limited by the specified options
+///
+///# Parameters and Return Type
+///
+/// `travel_times` is a `travelling_salesman::oracle::DistanceOracle`, containing the time taken
+/// to travel between each pair of cities.
+///
+/// `service_times` is an array slice, containing the time spent at each city.
+///
+/// `time_windows` is an array slice, containing an `(earliest, latest)` tuple for each city
+/// specifying when its service may start. The window of the depot specifies when the route may
+/// leave and when it must have returned.
+///
+/// `depot` is the city the route starts and finishes at.
+///
+/// `objective` is a `travelling_salesman::time_windows::Objective`, specifying what to minimise.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `travelling_salesman::time_windows::TimedTour` struct, representing the approximate
+/// solution found, or `travelling_salesman::Error::Infeasible` if some city can never be served
+/// within its window, or no feasible route was found within the runtime.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::time_windows::Objective;
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(10, 100.0, 7);
+///  let travel_times = travelling_salesman::get_distance_matrix(&cities);
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(10_000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::time_windows::solve_with_options(
+///      &travel_times,
+///      &[5.0; 10],
+///      &[(0.0, 2000.0); 10],
+///      0,
+///      Objective::Makespan,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    travel_times: &D,
+    service_times: &[f64],
+    time_windows: &[(f64, f64)],
+    depot: usize,
+    objective: Objective,
+    runtime: Duration,
+    options: &Options,
+) -> Result<TimedTour, Error> {
+    options.validate_search_only()?;
+
+    let size = travel_times.size();
+
+    if service_times.len() != size || time_windows.len() != size {
+        return Err(Error::InvalidOptions(format!(
+            "expected {} service times and time windows, but got {} and {}",
+            size,
+            service_times.len(),
+            time_windows.len()
+        )));
+    }
+
+    validate_city(depot, size)?;
+
+    let schedule = Schedule {
+        travel_times,
+        service_times,
+        time_windows,
+        depot,
+        objective,
+    };
+
+    schedule.check_reachable()?;
+
+    // start from the cities ordered by the closing of their windows, which is feasible whenever
+    // the windows are loose enough
+
+    let mut route: Vec<usize> = (0..size).filter(|&city| city != depot).collect();
+    route.sort_by(|&a, &b| {
+        time_windows[a]
+            .1
+            .partial_cmp(&time_windows[b].1)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut current = schedule.evaluate(route);
+    let mut best = current.clone();
+    let mut rng = options.rng();
+    let mut budget = options.budget(runtime);
+    let mut stale_iterations = 0;
+
+    while current.route.len() >= 2 && budget.next() {
+        let (changed_from, next_route) = random_move(&mut rng, &current.route);
+        let bound = if current.lateness == 0.0 {
+            Some(0.0)
+        } else {
+            None
+        };
+
+        match schedule.evaluate_from(&current, next_route, changed_from, bound) {
+            Some(next) if next.cost() <= current.cost() => {
+                current = next;
+                stale_iterations = 0;
+            }
+            _ => stale_iterations += 1,
+        }
+
+        if current.cost() < best.cost() {
+            best = current.clone();
+        }
+
+        // escape local optima by perturbing the best route found so far
+
+        if stale_iterations > 50 * current.route.len() {
+            let mut route = best.route.clone();
+
+            for _ in 0..3 {
+                route = random_relocation(&mut rng, &route).1;
+            }
+
+            current = schedule.evaluate(route);
+            stale_iterations = 0;
+        }
+    }
+
+    if best.lateness > 0.0 {
+        return Err(Error::Infeasible(
+            "no route meeting every time window was found within the runtime".to_string(),
+        ));
+    }
+
+    Ok(schedule.timed_tour(&best.route))
+}
+
+/// The problem data needed to schedule a route
+struct Schedule<'a, D: DistanceOracle + ?Sized> {
+    travel_times: &'a D,
+    service_times: &'a [f64],
+    time_windows: &'a [(f64, f64)],
+    depot: usize,
+    objective: Objective,
+}
+
+/// A route of every city except the depot, with its schedule cached for incremental evaluation
+#[derive(Clone)]
+struct State {
+    route: Vec<usize>,
+    /// the time service finished at each city of the route
+    departures: Vec<f64>,
+    /// the total lateness up to and including each city of the route
+    prefix_lateness: Vec<f64>,
+    /// the total travel time up to and including each city of the route
+    prefix_travel: Vec<f64>,
+    lateness: f64,
+    value: f64,
+}
+
+impl State {
+    /// Infeasible routes always cost more than feasible routes
+    fn cost(&self) -> (f64, f64) {
+        (self.lateness, self.value)
+    }
+}
+
+impl<'a, D: DistanceOracle + ?Sized> Schedule<'a, D> {
+    fn travel(&self, from: usize, to: usize) -> f64 {
+        self.travel_times.distance(from, to).to_f64()
+    }
+
+    /// Returns an error if some city cannot be served within its window, even when visited
+    /// directly from the depot
+    fn check_reachable(&self) -> Result<(), Error> {
+        let (departure, closing) = self.time_windows[self.depot];
+
+        for city in (0..self.time_windows.len()).filter(|&city| city != self.depot) {
+            let (earliest, latest) = self.time_windows[city];
+            let arrival = departure + self.travel(self.depot, city);
+
+            if arrival > latest {
+                return Err(Error::Infeasible(format!(
+                    "city {} cannot be reached before its window closes at {}",
+                    city, latest
+                )));
+            }
+
+            let service_start = arrival.max(earliest);
+            let finish = service_start + self.service_times[city] + self.travel(city, self.depot);
+
+            if finish > closing {
+                return Err(Error::Infeasible(format!(
+                    "city {} cannot be served before the depot closes at {}",
+                    city, closing
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&self, route: Vec<usize>) -> State {
+        let empty = State {
+            route: vec![],
+            departures: vec![],
+            prefix_lateness: vec![],
+            prefix_travel: vec![],
+            lateness: 0.0,
+            value: 0.0,
+        };
+
+        self.evaluate_from(&empty, route, 0, None)
+            .expect("unbounded evaluation always succeeds")
+    }
+
+    /// Evaluates `route`, which matches `previous.route` before position `changed_from`
+    ///
+    /// If `bound` is given, returns `None` as soon as the lateness exceeds it.
+    fn evaluate_from(
+        &self,
+        previous: &State,
+        route: Vec<usize>,
+        changed_from: usize,
+        bound: Option<f64>,
+    ) -> Option<State> {
+        let mut departures = previous.departures[..changed_from].to_vec();
+        let mut prefix_lateness = previous.prefix_lateness[..changed_from].to_vec();
+        let mut prefix_travel = previous.prefix_travel[..changed_from].to_vec();
+
+        let (mut time, mut lateness, mut travel, mut city) = if changed_from == 0 {
+            (self.time_windows[self.depot].0, 0.0, 0.0, self.depot)
+        } else {
+            (
+                departures[changed_from - 1],
+                prefix_lateness[changed_from - 1],
+                prefix_travel[changed_from - 1],
+                route[changed_from - 1],
+            )
+        };
+
+        for &next_city in &route[changed_from..] {
+            let (earliest, latest) = self.time_windows[next_city];
+            let leg = self.travel(city, next_city);
+            let service_start = (time + leg).max(earliest);
+
+            lateness += (service_start - latest).max(0.0);
+
+            if bound.is_some_and(|bound| lateness > bound) {
+                return None;
+            }
+
+            travel += leg;
+            time = service_start + self.service_times[next_city];
+            city = next_city;
+
+            departures.push(time);
+            prefix_lateness.push(lateness);
+            prefix_travel.push(travel);
+        }
+
+        let leg = self.travel(city, self.depot);
+        let (departure, closing) = self.time_windows[self.depot];
+
+        lateness += (time + leg - closing).max(0.0);
+
+        if bound.is_some_and(|bound| lateness > bound) {
+            return None;
+        }
+
+        let value = match self.objective {
+            Objective::Makespan => time + leg - departure,
+            Objective::TravelTime => travel + leg,
+        };
+
+        Some(State {
+            route,
+            departures,
+            prefix_lateness,
+            prefix_travel,
+            lateness,
+            value,
+        })
+    }
+
+    fn timed_tour(&self, route: &[usize]) -> TimedTour {
+        let (departure, _) = self.time_windows[self.depot];
+        let mut full_route = vec![self.depot];
+        full_route.extend_from_slice(route);
+        full_route.push(self.depot);
+
+        let mut arrivals = vec![departure];
+        let mut service_starts = vec![departure];
+        let mut time = departure;
+        let mut distance = 0.0;
+
+        for leg in full_route.windows(2) {
+            let travel = self.travel(leg[0], leg[1]);
+            let arrival = time + travel;
+            let service_start = if leg[1] == self.depot {
+                arrival
+            } else {
+                arrival.max(self.time_windows[leg[1]].0)
+            };
+
+            distance += travel;
+            time = service_start + self.service_times[leg[1]];
+
+            arrivals.push(arrival);
+            service_starts.push(service_start);
+        }
+
+        TimedTour {
+            makespan: arrivals[arrivals.len() - 1] - departure,
+            tour: Tour {
+                distance,
+                route: full_route,
+                closed: true,
+            },
+            arrivals,
+            service_starts,
+        }
+    }
+}
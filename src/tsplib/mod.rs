@@ -0,0 +1,357 @@
+//! Read problem instances and write tours in the TSPLIB format
+//!
+//! This covers symmetric and asymmetric TSP instances, as well as the capacitated vehicle routing
+//! instances of CVRPLIB (`.vrp` files), which extend TSPLIB with vehicle capacity, demands and
+//! depots.
+//!
+//! For more information, please see the [TSPLIB
+//! documentation](http://comopt.ifi.uni-heidelberg.de/software/TSPLIB95/tsp95.pdf) and
+//! [CVRPLIB](http://vrp.galgos.inf.puc-rio.br/).
+//!
+//!# Examples
+//!
+//!```
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let instance = travelling_salesman::tsplib::parse(
+//!    "NAME : example
+//!     TYPE : TSP
+//!     DIMENSION : 5
+//!     EDGE_WEIGHT_TYPE : EUC_2D
+//!     NODE_COORD_SECTION
+//!     1 27 78
+//!     2 18 24
+//!     3 48 62
+//!     4 83 77
+//!     5 55 56
+//!     EOF",
+//!  )
+//!  .unwrap();
+//!
+//!  let tour = travelling_salesman::brute_force::solve_with_oracle(
+//!    &instance.distance_matrix().unwrap(),
+//!  );
+//!
+//!  assert_eq!(tour.distance, 203);
+//!}
+//!```
+//!
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use super::matrix::{DistanceMatrix, Weight};
+use super::metrics::{CeilingEuclidean, Chebyshev, Geo, Manhattan, Metric, RoundedEuclidean};
+use super::{Error, Tour};
+
+/// A problem instance read from a TSPLIB file
+///
+/// Cities are numbered from zero, rather than from one as in the file.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Instance {
+    /// the name of the instance
+    pub name: String,
+    /// the type of problem, such as `TSP`, `ATSP` or `CVRP`
+    pub kind: String,
+    /// the number of cities
+    pub dimension: usize,
+    /// how distances are calculated, such as `EUC_2D`, `GEO` or `EXPLICIT`
+    pub edge_weight_type: String,
+    /// how explicit distances are laid out, such as `FULL_MATRIX` or `UPPER_ROW`
+    pub edge_weight_format: Option<String>,
+    /// the capacity of each vehicle, for vehicle routing instances
+    pub capacity: Option<u64>,
+    /// the coordinates of each city, if given
+    pub coordinates: Vec<(f64, f64)>,
+    /// the explicit distances, in the order they appear in the file
+    pub edge_weights: Vec<f64>,
+    /// the demand of each city, for vehicle routing instances
+    pub demands: Vec<u64>,
+    /// the depot cities, for vehicle routing instances
+    pub depots: Vec<usize>,
+}
+
+impl Instance {
+    /// Returns the distance matrix of the instance, with integer distances as TSPLIB defines them
+    pub fn distance_matrix(&self) -> Result<DistanceMatrix<i64>, Error> {
+        let n = self.dimension;
+
+        if self.edge_weight_type == "EXPLICIT" {
+            return self.explicit_distance_matrix();
+        }
+
+        if self.coordinates.len() != n {
+            return Err(Error::Parse(format!(
+                "expected {} coordinates, but found {}",
+                n,
+                self.coordinates.len()
+            )));
+        }
+
+        let cities = &self.coordinates;
+
+        match self.edge_weight_type.as_str() {
+            "EUC_2D" => Ok(DistanceMatrix::from_metric(
+                cities,
+                &RoundedEuclidean::new(),
+            )),
+            "CEIL_2D" => Ok(DistanceMatrix::from_metric(
+                cities,
+                &CeilingEuclidean::new(),
+            )),
+            "GEO" => Ok(DistanceMatrix::from_metric(cities, &Geo)),
+            "MAN_2D" => Ok(DistanceMatrix::from_fn(n, |from, to| {
+                i64::from_f64(Manhattan::new().distance(&cities[from], &cities[to]))
+            })),
+            "MAX_2D" => Ok(DistanceMatrix::from_fn(n, |from, to| {
+                i64::from_f64(Chebyshev::new().distance(&cities[from], &cities[to]))
+            })),
+            "ATT" => Ok(DistanceMatrix::from_fn(n, |from, to| {
+                pseudo_euclidean(&cities[from], &cities[to])
+            })),
+            other => Err(Error::Parse(format!(
+                "unsupported edge weight type {}",
+                other
+            ))),
+        }
+    }
+
+    fn explicit_distance_matrix(&self) -> Result<DistanceMatrix<i64>, Error> {
+        let n = self.dimension;
+        let format = self.edge_weight_format.as_deref().unwrap_or("FULL_MATRIX");
+
+        // column-wise formats list the same entries as the opposite row-wise format
+
+        let (upper, diagonal) = match format {
+            "FULL_MATRIX" => {
+                if self.edge_weights.len() != n * n {
+                    return Err(Error::Parse(format!(
+                        "expected {} edge weights, but found {}",
+                        n * n,
+                        self.edge_weights.len()
+                    )));
+                }
+
+                return Ok(DistanceMatrix::from_fn(n, |from, to| {
+                    i64::from_f64(self.edge_weights[from * n + to])
+                }));
+            }
+            "UPPER_ROW" | "LOWER_COL" => (true, false),
+            "LOWER_ROW" | "UPPER_COL" => (false, false),
+            "UPPER_DIAG_ROW" | "LOWER_DIAG_COL" => (true, true),
+            "LOWER_DIAG_ROW" | "UPPER_DIAG_COL" => (false, true),
+            other => {
+                return Err(Error::Parse(format!(
+                    "unsupported edge weight format {}",
+                    other
+                )))
+            }
+        };
+
+        let mut matrix = DistanceMatrix::from_fn(n, |_, _| 0);
+        let mut weights = self.edge_weights.iter();
+
+        for row in 0..n {
+            let columns = match (upper, diagonal) {
+                (true, true) => row..n,
+                (true, false) => (row + 1)..n,
+                (false, true) => 0..(row + 1),
+                (false, false) => 0..row,
+            };
+
+            for column in columns {
+                let weight = match weights.next() {
+                    Some(&weight) => i64::from_f64(weight),
+                    None => {
+                        return Err(Error::Parse(
+                            "too few edge weights for the dimension".to_string(),
+                        ))
+                    }
+                };
+
+                matrix.set(row, column, weight);
+                matrix.set(column, row, weight);
+            }
+        }
+
+        Ok(matrix)
+    }
+}
+
+/// The TSPLIB `ATT` pseudo-Euclidean distance
+fn pseudo_euclidean(from: &(f64, f64), to: &(f64, f64)) -> i64 {
+    let r = (((from.0 - to.0).powi(2) + (from.1 - to.1).powi(2)) / 10.0).sqrt();
+    let t = i64::from_f64(r);
+
+    if (t as f64) < r {
+        t + 1
+    } else {
+        t
+    }
+}
+
+/// Parses a TSPLIB instance from the contents of a file
+pub fn parse(text: &str) -> Result<Instance, Error> {
+    let mut instance = Instance::default();
+    let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let mut section: Option<String> = None;
+
+    loop {
+        let line = match section.take() {
+            Some(line) => line,
+            None => match lines.next() {
+                Some(line) => line.to_string(),
+                None => break,
+            },
+        };
+
+        if line == "EOF" {
+            break;
+        }
+
+        if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+
+            match key.trim() {
+                "NAME" => instance.name = value.to_string(),
+                "TYPE" => instance.kind = value.to_string(),
+                "DIMENSION" => instance.dimension = parse_number(value)?,
+                "CAPACITY" => instance.capacity = Some(parse_number(value)?),
+                "EDGE_WEIGHT_TYPE" => instance.edge_weight_type = value.to_string(),
+                "EDGE_WEIGHT_FORMAT" => instance.edge_weight_format = Some(value.to_string()),
+                _ => {}
+            }
+
+            continue;
+        }
+
+        // sections continue until the next keyword, which starts with a letter
+
+        let mut rows = vec![];
+
+        for next_line in lines.by_ref() {
+            if next_line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                section = Some(next_line.to_string());
+                break;
+            }
+
+            rows.push(next_line);
+        }
+
+        match line.as_str() {
+            "NODE_COORD_SECTION" => {
+                for row in rows {
+                    let fields = parse_fields::<f64>(row)?;
+
+                    if fields.len() != 3 {
+                        return Err(Error::Parse(format!(
+                            "expected a 2-D coordinate, but found \"{}\"",
+                            row
+                        )));
+                    }
+
+                    instance.coordinates.push((fields[1], fields[2]));
+                }
+            }
+            "EDGE_WEIGHT_SECTION" => {
+                for row in rows {
+                    instance.edge_weights.extend(parse_fields::<f64>(row)?);
+                }
+            }
+            "DEMAND_SECTION" => {
+                for row in rows {
+                    let fields = parse_fields::<u64>(row)?;
+
+                    if fields.len() != 2 {
+                        return Err(Error::Parse(format!(
+                            "expected a demand, but found \"{}\"",
+                            row
+                        )));
+                    }
+
+                    instance.demands.push(fields[1]);
+                }
+            }
+            "DEPOT_SECTION" => {
+                for row in rows {
+                    for depot in parse_fields::<i64>(row)? {
+                        if depot < 1 {
+                            break;
+                        }
+
+                        instance.depots.push(depot as usize - 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(instance)
+}
+
+/// Reads a TSPLIB instance from the file at `path`
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Instance, Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => parse(&text),
+        Err(error) => Err(Error::Io(error.to_string())),
+    }
+}
+
+/// Returns a tour in the TSPLIB `TOUR` format, with cities numbered from one
+///
+/// The cities are listed in the order they are visited, without returning to the first city of a
+/// closed tour, and followed by `-1`.
+///
+///# Examples
+///
+///```
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Tour;
+///
+///fn main() {
+///  let tour = Tour {
+///    distance: 12,
+///    route: vec![0, 2, 1, 0],
+///    closed: true,
+///  };
+///
+///  assert_eq!(
+///    travelling_salesman::tsplib::format_tour("example", &tour),
+///    "NAME : example\nTYPE : TOUR\nDIMENSION : 3\nTOUR_SECTION\n1\n3\n2\n-1\nEOF\n",
+///  );
+///}
+///```
+pub fn format_tour<W>(name: &str, tour: &Tour<W>) -> String {
+    let stops = tour.stops();
+    let mut text = format!(
+        "NAME : {}\nTYPE : TOUR\nDIMENSION : {}\nTOUR_SECTION\n",
+        name,
+        stops.len()
+    );
+
+    for &city in stops {
+        text.push_str(&format!("{}\n", city + 1));
+    }
+
+    text.push_str("-1\nEOF\n");
+    text
+}
+
+/// Writes a tour in the TSPLIB `TOUR` format to the file at `path`
+pub fn write_tour<W, P: AsRef<Path>>(path: P, name: &str, tour: &Tour<W>) -> Result<(), Error> {
+    fs::write(path, format_tour(name, tour)).map_err(|error| Error::Io(error.to_string()))
+}
+
+fn parse_number<T: FromStr>(value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::Parse(format!("invalid number \"{}\"", value)))
+}
+
+fn parse_fields<T: FromStr>(row: &str) -> Result<Vec<T>, Error> {
+    row.split_whitespace().map(parse_number).collect()
+}
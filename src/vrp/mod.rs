@@ -0,0 +1,376 @@
+//! Find an approximate solution to the Capacitated Vehicle Routing Problem
+//!
+//! Each city other than the depot has a demand, which must be delivered by exactly one vehicle.
+//! Every vehicle starts and finishes at the depot, and the total demand of the cities it visits
+//! must not exceed its capacity.
+//!
+//! Routes are built using the Clarke-Wright savings heuristic, then improved by moving cities
+//! between routes (relocate, exchange and 2-opt*), and by solving each route as a Travelling
+//! Salesman Problem using this crate's solvers.
+//!
+//!# Examples
+//!
+//!```
+//!extern crate time;
+//!extern crate travelling_salesman;
+//!
+//!fn main() {
+//!  let instance = travelling_salesman::tsplib::parse(
+//!    "NAME : example
+//!     TYPE : CVRP
+//!     DIMENSION : 6
+//!     EDGE_WEIGHT_TYPE : EUC_2D
+//!     CAPACITY : 10
+//!     NODE_COORD_SECTION
+//!     1 50 50
+//!     2 10 10
+//!     3 20 10
+//!     4 90 90
+//!     5 80 90
+//!     6 90 80
+//!     DEMAND_SECTION
+//!     1 0
+//!     2 5
+//!     3 5
+//!     4 4
+//!     5 3
+//!     6 3
+//!     DEPOT_SECTION
+//!     1
+//!     -1
+//!     EOF",
+//!  )
+//!  .unwrap();
+//!
+//!  let tours = travelling_salesman::vrp::solve(
+//!    &instance.distance_matrix().unwrap(),
+//!    &instance.demands,
+//!    instance.capacity.unwrap(),
+//!    instance.depots[0],
+//!    2,
+//!    time::Duration::seconds(1),
+//!  )
+//!  .unwrap();
+//!
+//!  for tour in tours {
+//!    println!("Vehicle distance: {}, route: {:?}", tour.distance, tour.route);
+//!  }
+//!}
+//!```
+//!
+use std::time::Instant;
+use time::Duration;
+
+use super::matrix::Weight;
+use super::oracle::DistanceOracle;
+use super::routes::{Balance, Fleet};
+use super::{get_route_distance, validate_city, Error, Options, Tour};
+
+/// Returns an approximate solution to the Capacitated Vehicle Routing Problem
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `demands` is an array slice, containing the demand of each city. The demand of the depot is
+/// ignored.
+///
+/// `capacity` is the capacity of each vehicle.
+///
+/// `depot` is the city every vehicle starts and finishes at.
+///
+/// `vehicles` is the number of vehicles in the fleet.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution.
+///
+/// Returns a `Vec` of `travelling_salesman::Tour` structs, one for each vehicle, where unused
+/// vehicles stay at the depot. Returns `travelling_salesman::Error::Infeasible` if some demand
+/// exceeds the capacity, or the cities could not be fitted into the fleet.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///fn main() {
+///  let cities = [
+///    (50.0, 50.0),
+///    (10.0, 10.0),
+///    (20.0, 10.0),
+///    (90.0, 90.0),
+///    (80.0, 90.0),
+///    (90.0, 80.0),
+///  ];
+///
+///  let tours = travelling_salesman::vrp::solve(
+///    &travelling_salesman::get_distance_matrix(&cities),
+///    &[0, 5, 5, 4, 3, 3],
+///    10,
+///    0,
+///    3,
+///    time::Duration::seconds(1),
+///  )
+///  .unwrap();
+///
+///  assert_eq!(tours.len(), 3);
+///  assert!(tours.iter().all(|tour| tour.route[0] == 0));
+///}
+///```
+pub fn solve<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    demands: &[u64],
+    capacity: u64,
+    depot: usize,
+    vehicles: usize,
+    runtime: Duration,
+) -> Result<Vec<Tour<D::Weight>>, Error> {
+    solve_with_options(
+        distances,
+        demands,
+        capacity,
+        depot,
+        vehicles,
+        runtime,
+        &Options::default(),
+    )
+}
+
+/// Returns an approximate solution to the Capacitated Vehicle Routing Problem, seeded and limited
+/// by the specified options
+///
+///# Parameters and Return Type
+///
+/// `distances` is a `travelling_salesman::oracle::DistanceOracle`, such as a distance matrix.
+///
+/// `demands` is an array slice, containing the demand of each city. The demand of the depot is
+/// ignored.
+///
+/// `capacity` is the capacity of each vehicle.
+///
+/// `depot` is the city every vehicle starts and finishes at.
+///
+/// `vehicles` is the number of vehicles in the fleet.
+///
+/// `runtime` is a `time::Duration`, specifying how long to spend searching for a solution, unless
+/// the options limit the number of iterations of each stage of the search instead.
+///
+/// `options` is a `travelling_salesman::Options` struct, of which only the seed and the number of
+/// iterations are supported. Setting any other option, such as fixed edges, returns
+/// `travelling_salesman::Error::InvalidOptions`.
+///
+/// Returns a `Vec` of `travelling_salesman::Tour` structs, one for each vehicle, where unused
+/// vehicles stay at the depot. Returns `travelling_salesman::Error::Infeasible` if some demand
+/// exceeds the capacity, or the cities could not be fitted into the fleet.
+///
+///# Examples
+///
+///```
+///extern crate time;
+///extern crate travelling_salesman;
+///
+///use travelling_salesman::Options;
+///
+///fn main() {
+///  let cities = travelling_salesman::generators::uniform(30, 100.0, 7);
+///  let distance_matrix = travelling_salesman::get_distance_matrix(&cities);
+///  let demands: Vec<u64> = (0..30).map(|city| city % 4 + 1).collect();
+///
+///  let options = Options {
+///    seed: Some(42),
+///    iterations: Some(1000),
+///    ..Options::default()
+///  };
+///
+///  let solve = || {
+///    travelling_salesman::vrp::solve_with_options(
+///      &distance_matrix,
+///      &demands,
+///      20,
+///      0,
+///      5,
+///      time::Duration::seconds(1),
+///      &options,
+///    )
+///    .unwrap()
+///  };
+///
+///  assert_eq!(solve(), solve());
+///}
+///```
+pub fn solve_with_options<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    demands: &[u64],
+    capacity: u64,
+    depot: usize,
+    vehicles: usize,
+    runtime: Duration,
+    options: &Options,
+) -> Result<Vec<Tour<D::Weight>>, Error> {
+    options.validate_search_only()?;
+
+    let size = distances.size();
+
+    if demands.len() != size {
+        return Err(Error::InvalidOptions(format!(
+            "expected {} demands, but got {}",
+            size,
+            demands.len()
+        )));
+    }
+
+    validate_city(depot, size)?;
+
+    for city in (0..size).filter(|&city| city != depot) {
+        if demands[city] > capacity {
+            return Err(Error::Infeasible(format!(
+                "the demand of city {} exceeds the vehicle capacity",
+                city
+            )));
+        }
+    }
+
+    let routes = savings(distances, demands, capacity, depot, vehicles);
+
+    if routes.len() > vehicles {
+        return Err(Error::Infeasible(format!(
+            "could not fit the cities into {} vehicles",
+            vehicles
+        )));
+    }
+
+    let depots = vec![depot; routes.len()];
+    let mut fleet = Fleet::new(distances, demands, capacity, Balance::Sum, depots, routes);
+
+    let start_time = Instant::now();
+
+    fleet.improve(&mut options.budget(runtime / 2));
+
+    let remaining = runtime - start_time.elapsed();
+    fleet.optimise_routes(remaining / (fleet.routes.len().max(1) as u32), options);
+    fleet.improve(&mut options.budget(runtime - start_time.elapsed()));
+
+    let mut tours: Vec<Tour<D::Weight>> = fleet
+        .routes
+        .iter()
+        .enumerate()
+        .filter(|&(_, route)| !route.is_empty())
+        .map(|(index, _)| {
+            let route = fleet.closed_route(index);
+
+            Tour {
+                distance: get_route_distance(distances, &route),
+                route,
+                closed: true,
+            }
+        })
+        .collect();
+
+    while tours.len() < vehicles {
+        tours.push(Tour {
+            distance: D::Weight::zero(),
+            route: vec![depot, depot],
+            closed: true,
+        });
+    }
+
+    Ok(tours)
+}
+
+/// Builds routes by repeatedly joining the end of one route to the start of another, in
+/// order of the distance saved by not returning to the depot in between
+///
+/// When the distances are symmetric, a route can be travelled in either direction, so either end
+/// of one route may be joined to either end of another, reversing them as needed.
+///
+/// Joins which save nothing are only made while there are more routes than `vehicles`.
+fn savings<D: DistanceOracle + ?Sized>(
+    distances: &D,
+    demands: &[u64],
+    capacity: u64,
+    depot: usize,
+    vehicles: usize,
+) -> Vec<Vec<usize>> {
+    let distance = |from: usize, to: usize| distances.distance(from, to).to_f64();
+    let size = distances.size();
+    let customers: Vec<usize> = (0..size).filter(|&city| city != depot).collect();
+
+    let symmetric =
+        (0..size).all(|from| (0..from).all(|to| distance(from, to) == distance(to, from)));
+
+    let mut routes: Vec<Option<Vec<usize>>> =
+        customers.iter().map(|&city| Some(vec![city])).collect();
+    let mut loads: Vec<u64> = customers.iter().map(|&city| demands[city]).collect();
+    let mut route_of = vec![0; size];
+    let mut route_count = customers.len();
+
+    for (index, &city) in customers.iter().enumerate() {
+        route_of[city] = index;
+    }
+
+    let mut savings = Vec::with_capacity(customers.len() * customers.len());
+
+    for &i in &customers {
+        // with symmetric distances, the saving of joining i to j is the same in either direction
+
+        for &j in customers
+            .iter()
+            .filter(|&&j| j != i && (!symmetric || j > i))
+        {
+            let saving = distance(i, depot) + distance(depot, j) - distance(i, j);
+            savings.push((saving, i, j));
+        }
+    }
+
+    savings.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    for (saving, i, j) in savings {
+        if saving <= 0.0 && route_count <= vehicles {
+            break;
+        }
+
+        let (a, b) = (route_of[i], route_of[j]);
+
+        if a == b || loads[a] + loads[b] > capacity {
+            continue;
+        }
+
+        let (mut route_a, mut route_b) = match (routes[a].take(), routes[b].take()) {
+            (Some(route_a), Some(route_b)) => (route_a, route_b),
+            (route_a, route_b) => {
+                routes[a] = route_a;
+                routes[b] = route_b;
+                continue;
+            }
+        };
+
+        // turn the routes around so that i ends route a and j starts route b, which only matters
+        // when they can be joined
+
+        if symmetric && route_a.last() != Some(&i) {
+            route_a.reverse();
+        }
+
+        if symmetric && route_b.first() != Some(&j) {
+            route_b.reverse();
+        }
+
+        if route_a.last() != Some(&i) || route_b.first() != Some(&j) {
+            routes[a] = Some(route_a);
+            routes[b] = Some(route_b);
+            continue;
+        }
+
+        for &city in &route_b {
+            route_of[city] = a;
+        }
+
+        route_a.extend(route_b);
+        routes[a] = Some(route_a);
+
+        loads[a] += loads[b];
+        route_count -= 1;
+    }
+
+    routes.into_iter().flatten().collect()
+}
@@ -0,0 +1,423 @@
+//! Tests of the tsp-solve command, run as a separate process
+//!
+extern crate travelling_salesman;
+
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{self, Command, Output, Stdio};
+
+/// The corners of a square with sides of 10, whose shortest tour travels 40
+const SQUARE_TSPLIB: &str = "NAME: square
+TYPE: TSP
+DIMENSION: 4
+EDGE_WEIGHT_TYPE: EUC_2D
+NODE_COORD_SECTION
+1 0 0
+2 0 10
+3 10 10
+4 10 0
+EOF
+";
+
+const SQUARE_CSV: &str = "x,y\n0,0\n0,10\n10,10\n10,0\n";
+
+/// Returns a path in the temporary directory, unique to this test run
+fn scratch(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("tsp-solve-{}-{}", process::id(), name))
+}
+
+/// Writes `contents` to a scratch file called `name`, returning its path
+fn write_input(name: &str, contents: &str) -> String {
+    let path = scratch(name);
+    fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_string()
+}
+
+fn tsp_solve(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_tsp-solve"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn tsp_solve_with_stdin(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_tsp-solve"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+/// Returns the value of a line of the summary, such as `distance`
+fn summary_value(text: &str, name: &str) -> String {
+    text.lines()
+        .find(|line| line.starts_with(&format!("{}:", name)))
+        .map(|line| line[name.len() + 1..].trim().to_string())
+        .unwrap_or_else(|| panic!("no {} in {:?}", name, text))
+}
+
+/// Checks the command failed with `code`, printing a message containing `message`
+fn assert_fails(output: &Output, code: i32, message: &str) {
+    assert_eq!(output.status.code(), Some(code), "{}", stderr(output));
+    assert!(
+        stderr(output).contains(message),
+        "expected {:?} in {:?}",
+        message,
+        stderr(output)
+    );
+}
+
+#[test]
+fn help_is_printed() {
+    for flag in &["-h", "--help"] {
+        let output = tsp_solve(&[flag]);
+
+        assert!(output.status.success());
+        assert!(stdout(&output).starts_with("Usage: tsp-solve"));
+    }
+}
+
+#[test]
+fn reads_tsplib() {
+    let input = write_input("square.tsp", SQUARE_TSPLIB);
+    let output = tsp_solve(&["--solver", "brute_force", &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(summary_value(&stdout(&output), "solver"), "brute_force");
+    assert_eq!(summary_value(&stdout(&output), "cities"), "4");
+    assert_eq!(summary_value(&stdout(&output), "distance"), "40");
+}
+
+#[test]
+fn reads_csv() {
+    let with_header = write_input("square.csv", SQUARE_CSV);
+    let without_header = write_input("square-plain.csv", "0,0\n0,10\n10,10\n10,0\n");
+
+    for input in &[with_header, without_header] {
+        let output = tsp_solve(&["-s", "brute_force", input]);
+
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_eq!(summary_value(&stdout(&output), "distance"), "40");
+    }
+}
+
+#[test]
+fn reads_geographic_csv() {
+    // a degree of longitude along the equator is about 111 kilometres
+
+    let input = write_input("equator.csv", "lat,lon\n0,0\n0,1\n");
+    let output = tsp_solve(&["-s", "brute_force", &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let distance: f64 = summary_value(&stdout(&output), "distance").parse().unwrap();
+    assert!((distance - 2.0 * 111.19).abs() < 1.0, "{}", distance);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn reads_json() {
+    let input = write_input(
+        "square.json",
+        r#"{
+          "cities": [[0, 0], [0, 10], [10, 10], [10, 0]],
+          "solver": "brute_force"
+        }"#,
+    );
+    let output = tsp_solve(&[&input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(summary_value(&stdout(&output), "solver"), "brute_force");
+    assert_eq!(summary_value(&stdout(&output), "distance"), "40");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn reads_json_with_a_minkowski_metric() {
+    // the triangle travels 12 with Euclidean distances, but 14 with Manhattan ones
+
+    let input = write_input(
+        "triangle.json",
+        r#"{
+          "cities": [[0, 0], [3, 4], [3, 0]],
+          "metric": {"minkowski": {"p": 1}},
+          "solver": "brute_force"
+        }"#,
+    );
+    let output = tsp_solve(&[&input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(summary_value(&stdout(&output), "distance"), "14");
+}
+
+#[cfg(not(feature = "serde"))]
+#[test]
+fn json_needs_the_serde_feature() {
+    let input = write_input("square.json", "{}");
+
+    assert_fails(&tsp_solve(&[&input]), 1, "serde feature");
+}
+
+#[test]
+fn reads_standard_input_with_a_format() {
+    let output = tsp_solve_with_stdin(&["-s", "brute_force", "--format", "csv", "-"], SQUARE_CSV);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(summary_value(&stdout(&output), "distance"), "40");
+}
+
+#[test]
+fn accepts_values_after_equals_signs() {
+    let input = write_input("equals.csv", SQUARE_CSV);
+    let output = tsp_solve(&["--solver=hill_climbing", "--time=0.1", &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(summary_value(&stdout(&output), "solver"), "hill_climbing");
+}
+
+#[test]
+fn seeded_iterations_find_the_same_route() {
+    let cities: String = (0..30)
+        .map(|city| format!("{},{}\n", city * 7 % 31, city * 13 % 37))
+        .collect();
+    let input = write_input("seeded.csv", &cities);
+
+    let route = || {
+        let output = tsp_solve(&["--seed", "7", "--iterations", "2000", &input]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        summary_value(&stdout(&output), "route")
+    };
+
+    assert_eq!(route(), route());
+}
+
+#[test]
+fn paths_start_and_end_at_the_given_cities() {
+    let input = write_input("path.csv", SQUARE_CSV);
+    let output = tsp_solve(&["-s", "brute_force", "--start", "1", "--end", "2", &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(summary_value(&stdout(&output), "distance"), "30");
+    assert_eq!(summary_value(&stdout(&output), "route"), "1 0 3 2");
+}
+
+#[test]
+fn prints_the_gap_to_the_optimum() {
+    let input = write_input("optimum.csv", SQUARE_CSV);
+    let output = tsp_solve(&["-s", "brute_force", "--optimum", "32", &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(
+        summary_value(&stdout(&output), "optimum"),
+        "32 (gap 25.00%)"
+    );
+}
+
+#[test]
+fn writes_tsplib() {
+    let input = write_input("tour.csv", SQUARE_CSV);
+    let tour = scratch("square.tour");
+    let output = tsp_solve(&["-s", "brute_force", "-o", tour.to_str().unwrap(), &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let text = fs::read_to_string(&tour).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert!(lines.contains(&"TYPE : TOUR"), "{}", text);
+    assert!(lines.contains(&"DIMENSION : 4"), "{}", text);
+    assert!(text.contains("TOUR_SECTION\n1\n"), "{}", text);
+    assert!(text.ends_with("-1\nEOF\n"), "{}", text);
+}
+
+#[test]
+fn writes_csv() {
+    let input = write_input("visits.csv", SQUARE_CSV);
+    let visits = scratch("visits-out.csv");
+    let output = tsp_solve(&["-s", "brute_force", "-o", visits.to_str().unwrap(), &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let text = fs::read_to_string(&visits).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+
+    assert_eq!(lines[0], "stop,city,x,y");
+    assert_eq!(lines.len(), 6);
+    assert_eq!(lines[1], "0,0,0,0");
+    assert_eq!(lines[5], "4,0,0,0");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn writes_json() {
+    let input = write_input("solution.csv", SQUARE_CSV);
+    let solution = scratch("solution.json");
+    let output = tsp_solve(&[
+        "-s",
+        "brute_force",
+        "-o",
+        solution.to_str().unwrap(),
+        &input,
+    ]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let text = fs::read_to_string(&solution).unwrap();
+    let solution = travelling_salesman::json::Solution::from_json(&text).unwrap();
+
+    assert_eq!(solution.distance, 40.0);
+    assert_eq!(solution.bound, Some(40.0));
+    assert_eq!(solution.route.len(), 5);
+}
+
+#[test]
+fn writes_svg() {
+    let input = write_input("drawing.csv", SQUARE_CSV);
+    let drawing = scratch("drawing.svg");
+    let output = tsp_solve(&["-s", "brute_force", "-o", drawing.to_str().unwrap(), &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let text = fs::read_to_string(&drawing).unwrap();
+    assert!(text.starts_with("<svg"), "{}", text);
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn writes_png() {
+    let input = write_input("picture.csv", SQUARE_CSV);
+    let picture = scratch("picture.png");
+    let output = tsp_solve(&["-s", "brute_force", "-o", picture.to_str().unwrap(), &input]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(fs::read(&picture).unwrap().starts_with(b"\x89PNG"));
+}
+
+#[test]
+fn writes_standard_output_with_the_summary_on_standard_error() {
+    let input = write_input("stdout.csv", SQUARE_CSV);
+    let output = tsp_solve(&[
+        "-s",
+        "brute_force",
+        "-o",
+        "-",
+        "--output-format",
+        "csv",
+        &input,
+    ]);
+
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).starts_with("stop,city,x,y\n"));
+    assert_eq!(summary_value(&stderr(&output), "distance"), "40");
+}
+
+#[test]
+fn rejects_bad_arguments() {
+    // mistakes on the command line exit with 2, and print the usage
+
+    let cases: &[(&[&str], &str)] = &[
+        (
+            &["--colour", "red", "in.csv"],
+            "unknown option \"--colour\"",
+        ),
+        (&["in.csv", "--time"], "--time needs a value"),
+        (
+            &["--time", "soon", "in.csv"],
+            "invalid value \"soon\" for --time",
+        ),
+        (
+            &["--seed", "-1", "in.csv"],
+            "invalid value \"-1\" for --seed",
+        ),
+        (
+            &["--solver", "genetic", "in.csv"],
+            "unknown solver \"genetic\"",
+        ),
+        (&["--objective", "shortest", "in.csv"], "unknown objective"),
+        (&["in.csv", "out.csv"], "unexpected argument \"out.csv\""),
+    ];
+
+    for &(args, message) in cases {
+        let output = tsp_solve(args);
+
+        assert_fails(&output, 2, message);
+        assert!(stderr(&output).contains("Usage: tsp-solve"));
+    }
+}
+
+#[test]
+fn rejects_bad_problems() {
+    // problems which cannot be solved exit with 1
+
+    let csv = write_input("problems.csv", SQUARE_CSV);
+    let tsplib = write_input("problems.tsp", SQUARE_TSPLIB);
+    let unknown = write_input("problems.txt", SQUARE_CSV);
+    let missing = scratch("missing.csv");
+    let missing = missing.to_str().unwrap();
+    let bad_row = write_input("bad-row.csv", "x,y\n0,0\n0,ten\n");
+
+    let cases: &[(&[&str], &str)] = &[
+        (&[], "no input file given"),
+        (&[missing], "cannot read"),
+        (&[&unknown], "cannot tell the format"),
+        (&["--format", "xml", &csv], "unknown input format \"xml\""),
+        (&[&bad_row], "invalid coordinates in row 2"),
+        (&["--metric", "taxicab", &csv], "unknown metric \"taxicab\""),
+        (
+            &["--metric", "minkowski:half", &csv],
+            "invalid Minkowski order",
+        ),
+        (
+            &["--metric", "minkowski:0.5", &csv],
+            "Minkowski order must be at least 1",
+        ),
+        (
+            &["--metric", "manhattan", &tsplib],
+            "give their own distances",
+        ),
+        (&["--shape", "loop", &csv], "unknown shape \"loop\""),
+        (&["--end", "2", &csv], "--end needs --start"),
+        (&["--start", "9", &csv], "city 9 is not in the problem"),
+        (
+            &["-s", "brute_force", "-t", "1", &csv],
+            "neither --time nor --iterations",
+        ),
+        (
+            &["-s", "brute_force", "--iterations", "10", &csv],
+            "neither --time",
+        ),
+        (
+            &["-o", "out.xyz", &csv],
+            "cannot tell the format of \"out.xyz\"",
+        ),
+        (
+            &["-o", "-", "--output-format", "gif", &csv],
+            "unknown output format",
+        ),
+    ];
+
+    for &(args, message) in cases {
+        assert_fails(&tsp_solve(args), 1, message);
+    }
+}